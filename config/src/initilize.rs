@@ -7,6 +7,14 @@ use std::sync::Mutex;
 pub const TB_FAVOURATES_MUSIC: &str = "favourates_music";
 pub const TB_FAVOURATES_PLAYLIST: &str = "favourates_playlist";
 pub const TB_FAVOURATES_ARTIST: &str = "favourates_artist";
+// Videos whose skip segments have been fetched. Kept seperate so that videos without any segment
+// are also not fetched again
+pub const TB_SEGMENT_LOOKUPS: &str = "segment_lookups";
+pub const TB_SKIP_SEGMENTS: &str = "skip_segments";
+// Queries searched before. Latest ones are shown as suggestion in search box
+pub const TB_SEARCH_HISTORY: &str = "search_history";
+// Responses of invidious servers keyed by the path of request. See `HttpCache` in config
+pub const TB_HTTP_CACHE: &str = "http_cache";
 
 compute_static! {
     pub static ref CONFIG: Config = {
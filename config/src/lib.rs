@@ -49,7 +49,9 @@ where
 
 type Color = (u8, u8, u8);
 
+// Missing keys in config file (eg: config written by older version) are filled from default
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(default)]
 pub struct ShortcutsKeys {
     pub toggle_play: char,
     pub next: char,
@@ -66,6 +68,22 @@ pub struct ShortcutsKeys {
     pub favourates_remove: char,
     pub vol_increase: char,
     pub vol_decrease: char,
+    pub loudness: char,
+    pub audio_preset: char,
+    pub sleep_timer: char,
+    pub speed_up: char,
+    pub speed_down: char,
+    pub speed_reset: char,
+    pub autoplay: char,
+    pub artist_radio: char,
+    pub chapters: char,
+    pub next_chapter: char,
+    pub prev_chapter: char,
+    pub lyrics: char,
+    pub video: char,
+    pub trending_category: char,
+    pub trending_region: char,
+    pub community_add: char,
 }
 
 impl Default for ShortcutsKeys {
@@ -134,6 +152,46 @@ impl Default for ShortcutsKeys {
 
             // Same as vol_increase but decrease the volume
             vol_decrease: '-',
+
+            // Cycle between the loudness normalization mode. See `LoudnessMode`
+            loudness: 'l',
+
+            // Cycle between the presets defined in `AudioFilters`
+            audio_preset: 'e',
+
+            // Pick the sleep timer option. eg: 15 minutes, end of track etc
+            sleep_timer: 'z',
+
+            // Increase/decrease playback speed by `Constants::speed_step` or reset it to normal
+            speed_up: ']',
+            speed_down: '[',
+            speed_reset: '=',
+
+            // Turn autoplay on/off. See `Playback::autoplay`
+            autoplay: 'a',
+
+            // Start radio from focused artist in artistbar
+            artist_radio: 'm',
+
+            // Show chapters of playing track and move between them
+            chapters: 'k',
+            next_chapter: '.',
+            prev_chapter: ',',
+
+            // Show/hide the lyrics of playing track
+            lyrics: 'y',
+
+            // Show/hide video of playing track in seperate window
+            video: 'w',
+
+            // Show trending of next category. eg: music, gaming etc
+            trending_category: 't',
+
+            // Show the list of regions to pick from for trending
+            trending_region: 'g',
+
+            // Add the artist/playlist under cursor to community section of sidebar
+            community_add: 'o',
         }
     }
 }
@@ -200,6 +258,10 @@ pub struct Constants {
     // Amount to increase/decrease by
     pub volume_step: i8,
 
+    // Amount to increase/decrease playback speed by. 0.1 means 10% faster/slower per key stroke
+    #[serde(default = "default_speed_step")]
+    pub speed_step: f64,
+
     // When any search query is prefixed by these strings in search query,
     // it will only show the result music/playlist/artist
     // prefixed by [0] => only music search and so on
@@ -218,6 +280,7 @@ impl Default for Constants {
             seek_backward_secs: 10,
             region: String::from("NP"),
             volume_step: 10,
+            speed_step: default_speed_step(),
             search_by_type: [
                 String::from("music:"),
                 String::from("playlist:"),
@@ -227,6 +290,10 @@ impl Default for Constants {
     }
 }
 
+fn default_speed_step() -> f64 {
+    0.1
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 pub struct Servers {
     pub list: Vec<String>,
@@ -288,9 +355,30 @@ impl Default for Downloads {
     }
 }
 
+// Which kind of mpv the player is backed by.
+// Libmpv: link to libmpv and drive the player in-process
+// Ipc: spawn the `mpv` binary and talk to it over the JSON IPC unix socket. This is useful
+// on machines where only mpv binary is installed and libmpv is not available to link against
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PlayerBackend {
+    #[default]
+    Libmpv,
+    Ipc,
+}
+
+fn default_mpv_binary() -> String {
+    String::from("mpv")
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct MpvOptions {
     config_path: String,
+    #[serde(default)]
+    pub backend: PlayerBackend,
+    // Name or path of mpv executable. Only used when backend is `ipc`
+    #[serde(default = "default_mpv_binary")]
+    pub mpv_binary: String,
 }
 
 impl Default for MpvOptions {
@@ -301,6 +389,433 @@ impl Default for MpvOptions {
                 .as_path()
                 .to_string_lossy()
                 .to_string(),
+            backend: PlayerBackend::default(),
+            mpv_binary: default_mpv_binary(),
+        }
+    }
+}
+
+// How the loudness of tracks are evened out. YouTube uploads vary a lot in loudness so switching
+// between tracks may need volume adjustment without this
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum LoudnessMode {
+    // Play as it is
+    #[default]
+    Off,
+    // EBU R128 loudness normalization with ffmpeg `loudnorm` filter
+    Loudnorm,
+    // Dynamic normalization with ffmpeg `dynaudnorm` filter which adapts gain over time
+    Dynaudnorm,
+    // Per-track gain computed from the loudness reported by youtube (`loudnessDb`).
+    // Track is played as it is when loudness is not available
+    TrackGain,
+}
+
+impl LoudnessMode {
+    // Mode to switch to when cycling the mode with shortcut key
+    pub fn next(self) -> Self {
+        match self {
+            LoudnessMode::Off => LoudnessMode::Loudnorm,
+            LoudnessMode::Loudnorm => LoudnessMode::Dynaudnorm,
+            LoudnessMode::Dynaudnorm => LoudnessMode::TrackGain,
+            LoudnessMode::TrackGain => LoudnessMode::Off,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct Playback {
+    // Loudness mode to start with. Can be changed in runtime with shortcut key
+    pub loudness: LoudnessMode,
+    // When sleep timer goes off, volume is faded out within this many seconds before pausing
+    pub sleep_fade_secs: u64,
+    // Also quit the application after sleep timer pauses the playback
+    pub quit_on_sleep: bool,
+    // When last item of queue starts playing, append the related videos of it to the queue so that
+    // playback never stops
+    pub autoplay: bool,
+    // Related videos shorter or longer than this (in seconds) are not added by autoplay. These are
+    // most likely not music. eg: shorts, podcasts, full albums
+    pub autoplay_min_secs: u64,
+    pub autoplay_max_secs: u64,
+    // Music longer than this (in seconds) is not added to artist radio
+    pub mix_max_secs: u64,
+    // Preferred languages (in order) of captions used as lyrics when there is no local lrc file.
+    // eg: ["en", "hi"]
+    pub lyrics_languages: Vec<String>,
+    // Start with video shown in seperate player window. Can be toggled at runtime with shortcut key
+    pub video: bool,
+    // When video is turned on at runtime, go back to audio only when next track starts
+    pub video_current_track_only: bool,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Playback {
+            loudness: LoudnessMode::default(),
+            sleep_fade_secs: 30,
+            quit_on_sleep: false,
+            autoplay: false,
+            autoplay_min_secs: 60,
+            autoplay_max_secs: 900,
+            mix_max_secs: 600,
+            lyrics_languages: vec!["en".to_string()],
+            video: false,
+            video_current_track_only: true,
+        }
+    }
+}
+
+// Gain of single equalizer band. Maps to ffmpeg `equalizer` filter
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct EqualizerBand {
+    // Center frequency in Hz
+    pub frequency: u32,
+    // Gain in dB. Negative value cuts the band
+    pub gain: f32,
+    // Width of band in octave
+    #[serde(default = "default_band_width")]
+    pub width: f32,
+}
+
+fn default_band_width() -> f32 {
+    1.0
+}
+
+// A named preset that can be cycled with shortcut key. Either `filter` is used as it is or the
+// `bands` are converted to chain of equalizer filter. Preset with neither is flat
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct AudioPreset {
+    pub name: String,
+    // Filter string as accepted by mpv `af` command. eg: "lavfi=[bass=g=5]"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bands: Vec<EqualizerBand>,
+}
+
+impl AudioPreset {
+    fn from_bands(name: &str, bands: &[(u32, f32)]) -> Self {
+        AudioPreset {
+            name: name.to_string(),
+            filter: None,
+            bands: bands
+                .iter()
+                .map(|&(frequency, gain)| EqualizerBand {
+                    frequency,
+                    gain,
+                    width: default_band_width(),
+                })
+                .collect(),
+        }
+    }
+
+    // Filter string to be passed to mpv. None for flat preset which means no filter at all
+    pub fn to_filter(&self) -> Option<String> {
+        if let Some(filter) = &self.filter {
+            return Some(filter.trim().to_string());
+        }
+        if self.bands.is_empty() {
+            return None;
+        }
+
+        let equalizers = self
+            .bands
+            .iter()
+            .map(|band| {
+                format!(
+                    "equalizer=f={}:t=o:w={}:g={}",
+                    band.frequency, band.width, band.gain
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        Some(format!("lavfi=[{}]", equalizers))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Preset name cannot be empty".to_string());
+        }
+        if self.filter.is_some() && !self.bands.is_empty() {
+            return Err(format!(
+                "Preset `{}` should define either filter or bands but not both",
+                self.name
+            ));
+        }
+
+        if let Some(filter) = &self.filter {
+            validate_filter(filter.trim())
+                .map_err(|err| format!("Invalid filter in preset `{}`: {}", self.name, err))?;
+        }
+
+        for band in &self.bands {
+            if band.frequency == 0 || band.frequency > 20_000 {
+                return Err(format!(
+                    "Band frequency in preset `{}` should be between 1 and 20000 Hz. Found: {}",
+                    self.name, band.frequency
+                ));
+            }
+            if !(-30.0..=30.0).contains(&band.gain) {
+                return Err(format!(
+                    "Band gain in preset `{}` should be between -30 and 30 dB. Found: {}",
+                    self.name, band.gain
+                ));
+            }
+            if band.width <= 0.0 {
+                return Err(format!(
+                    "Band width in preset `{}` should be positive. Found: {}",
+                    self.name, band.width
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Check that the filter is single mpv filter of form `name` or `name=params`.
+// Multiple filter should be chained inside lavfi instead. eg: "lavfi=[bass=g=3,treble=g=2]"
+// This is because the filter is added with a label and only then it can be replaced as whole
+fn validate_filter(filter: &str) -> Result<(), String> {
+    let name = filter.split('=').next().unwrap_or_default();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+    {
+        return Err(format!("`{}` is not valid filter name", name));
+    }
+
+    let mut depth: usize = 0;
+    for ch in filter.chars() {
+        match ch {
+            '[' => depth += 1,
+            ']' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| "Unmatched `]`".to_string())?;
+            }
+            ',' if depth == 0 => {
+                return Err("Multiple filters should be chained inside lavfi=[..]".to_string())
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err("Unmatched `[`".to_string());
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct AudioFilters {
+    // Name of preset to start with
+    pub active: String,
+    pub presets: Vec<AudioPreset>,
+}
+
+impl Default for AudioFilters {
+    fn default() -> Self {
+        AudioFilters {
+            active: "flat".to_string(),
+            presets: vec![
+                AudioPreset::from_bands("flat", &[]),
+                AudioPreset::from_bands("bass_boost", &[(60, 6.0), (170, 4.0), (310, 2.0)]),
+                AudioPreset::from_bands(
+                    "vocal",
+                    &[(250, -2.0), (1000, 2.0), (3000, 4.0), (6000, 2.0)],
+                ),
+            ],
+        }
+    }
+}
+
+impl AudioFilters {
+    pub fn validate(&self) -> Result<(), String> {
+        for (index, preset) in self.presets.iter().enumerate() {
+            preset.validate()?;
+            if self.presets[..index]
+                .iter()
+                .any(|other| other.name == preset.name)
+            {
+                return Err(format!(
+                    "Preset `{}` is defined more than once",
+                    preset.name
+                ));
+            }
+        }
+
+        if !self.presets.is_empty() && self.position_of(&self.active).is_none() {
+            return Err(format!(
+                "Active preset `{}` is not one of defined presets",
+                self.active
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn position_of(&self, name: &str) -> Option<usize> {
+        self.presets.iter().position(|preset| preset.name == name)
+    }
+}
+
+// ISO codes of regions for which trending videos are available
+pub const TRENDING_REGIONS: [&str; 106] = [
+    "AE", "AR", "AT", "AU", "AZ", "BA", "BD", "BE", "BG", "BH", "BO", "BR", "BY", "CA", "CH", "CL",
+    "CO", "CR", "CY", "CZ", "DE", "DK", "DO", "DZ", "EC", "EE", "EG", "ES", "FI", "FR", "GB", "GE",
+    "GH", "GR", "GT", "HK", "HN", "HR", "HU", "ID", "IE", "IL", "IN", "IQ", "IS", "IT", "JM", "JO",
+    "JP", "KE", "KR", "KW", "KZ", "LB", "LI", "LK", "LT", "LU", "LV", "LY", "MA", "ME", "MK", "MT",
+    "MX", "MY", "NG", "NI", "NL", "NO", "NP", "NZ", "OM", "PA", "PE", "PG", "PH", "PK", "PL", "PR",
+    "PT", "PY", "QA", "RO", "RS", "RU", "SA", "SE", "SG", "SI", "SK", "SN", "SV", "TH", "TN", "TR",
+    "TW", "TZ", "UA", "UG", "US", "UY", "VN", "YE", "ZA", "ZW",
+];
+
+// Categories of segments known by SponsorBlock api
+pub const SEGMENT_CATEGORIES: [&str; 8] = [
+    "sponsor",
+    "selfpromo",
+    "interaction",
+    "intro",
+    "outro",
+    "preview",
+    "music_offtopic",
+    "filler",
+];
+
+// Skipping of non-music segments like intro or sponsor read in music videos.
+// Segments are fetched from SponsorBlock compatible api and kept in local database so that those
+// are also skipped when api cannot be reached
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct SegmentSkip {
+    // Off unless asked for as id of every played video is sent to the api
+    pub enabled: bool,
+    // Base url of api. /api/skipSegments is appended to it
+    pub api: String,
+    // Segments of these categories are skipped. Must be from `SEGMENT_CATEGORIES`
+    pub skip: Vec<String>,
+    // Segments stored in local database are fetched again after these many days
+    pub cache_days: u64,
+}
+
+impl Default for SegmentSkip {
+    fn default() -> Self {
+        SegmentSkip {
+            enabled: false,
+            api: "https://sponsor.ajay.app".to_string(),
+            skip: ["sponsor", "selfpromo", "interaction", "music_offtopic"]
+                .iter()
+                .map(|category| category.to_string())
+                .collect(),
+            cache_days: 7,
+        }
+    }
+}
+
+impl SegmentSkip {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(unknown) = self
+            .skip
+            .iter()
+            .find(|category| !SEGMENT_CATEGORIES.contains(&category.as_str()))
+        {
+            return Err(format!(
+                "Unknown category `{}`. Expected one of: {}",
+                unknown,
+                SEGMENT_CATEGORIES.join(", ")
+            ));
+        }
+        if self.enabled && !self.api.starts_with("http") {
+            return Err(format!("`{}` is not a valid api url", self.api));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    Channel,
+    Playlist,
+}
+
+// Channel or playlist listed in community section of sidebar
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct CommunitySource {
+    pub name: String,
+    // Id of channel or playlist as in youtube
+    pub id: String,
+    pub kind: SourceKind,
+    // Sources of same group are listed together under single entry of sidebar
+    pub group: String,
+}
+
+// Curated channels and playlists shown in their own section of sidebar. Selection can also be
+// added here from the ui which then is saved back in config file
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(default)]
+pub struct Community {
+    // Group to which the selection is added from ui
+    pub default_group: String,
+    pub sources: Vec<CommunitySource>,
+}
+
+impl Default for Community {
+    fn default() -> Self {
+        Community {
+            default_group: "Youtube Community".to_string(),
+            sources: vec![CommunitySource {
+                name: "Youtube Music Global Charts".to_string(),
+                id: "UCrKZcyOJVWnJ60zM1XWllNw".to_string(),
+                kind: SourceKind::Channel,
+                group: "Youtube Community".to_string(),
+            }],
+        }
+    }
+}
+
+impl Community {
+    // Name of groups in the order they first appear in sources
+    pub fn groups(&self) -> Vec<&str> {
+        let mut groups: Vec<&str> = Vec::new();
+        for source in self.sources.iter() {
+            if !groups.contains(&source.group.as_str()) {
+                groups.push(&source.group);
+            }
+        }
+        groups
+    }
+}
+
+// Responses of invidious servers are kept in local database so that same thing is not fetched
+// again in every session. Stored response is used until it is older than ttl of its endpoint.
+// Responses of other endpoints (eg: search) are never stored. All ttl are in seconds
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct HttpCache {
+    pub enabled: bool,
+    pub trending_ttl: u64,
+    pub playlist_ttl: u64,
+    pub channel_ttl: u64,
+    // Least recently used responses are removed once there are more than these many
+    pub max_entries: u64,
+}
+
+impl Default for HttpCache {
+    fn default() -> Self {
+        HttpCache {
+            enabled: true,
+            trending_ttl: 60 * 60,
+            playlist_ttl: 24 * 60 * 60,
+            channel_ttl: 6 * 60 * 60,
+            max_entries: 500,
         }
     }
 }
@@ -319,6 +834,16 @@ pub struct Config {
     pub mpv: MpvOptions,
     #[serde(default, rename = "Downloads")]
     pub download: Downloads,
+    #[serde(default, rename = "Playback")]
+    pub playback: Playback,
+    #[serde(default, rename = "AudioFilters")]
+    pub audio_filters: AudioFilters,
+    #[serde(default, rename = "SegmentSkip")]
+    pub segment_skip: SegmentSkip,
+    #[serde(default, rename = "HttpCache")]
+    pub http_cache: HttpCache,
+    #[serde(default, rename = "Community")]
+    pub community: Community,
 }
 
 impl Config {
@@ -416,6 +941,16 @@ impl ConfigContainer {
             return None;
         }
 
+        // Malformed filter is only known after mpv rejects it at runtime. Catch it early
+        if let Err(err) = config.audio_filters.validate() {
+            eprintln!("Config Error: In `AudioFilters`: {}", err);
+            return None;
+        }
+        if let Err(err) = config.segment_skip.validate() {
+            eprintln!("Config Error: In `SegmentSkip`: {}", err);
+            return None;
+        }
+
         // Check and update the download directory
         // If dir is not valid, program should continue as it not hard error
         if let Some(dow_path) = validate_dir(&config.download.path) {
@@ -446,10 +981,16 @@ impl ConfigContainer {
             None => return None,
         };
 
+        Self::write_file(&self.file_path, &content)
+    }
+
+    // Replace whatever is in config file with given content
+    fn write_file(file_path: &path::Path, content: &str) -> Option<()> {
         let mut file_handle = match std::fs::OpenOptions::new()
             .write(true)
             .create(true)
-            .open(&self.file_path)
+            .truncate(true)
+            .open(file_path)
         {
             Ok(val) => val,
             Err(err) => {
@@ -458,15 +999,13 @@ impl ConfigContainer {
             }
         };
 
-        match file_handle.write(content.as_bytes()) {
-            Ok(_) => {}
+        match file_handle.write_all(content.as_bytes()) {
+            Ok(_) => Some(()),
             Err(err) => {
                 eprintln!("unable to write config to file. Error: {}", err);
-                return None;
+                None
             }
         }
-
-        Some(())
     }
 
     pub fn get_config_dir() -> Option<path::PathBuf> {
@@ -545,10 +1084,38 @@ impl ConfigContainer {
                     name    TEXT    NOT NULL,
                     count   TEXT    NOT NULL
                 );
+
+                CREATE TABLE IF NOT EXISTS {tb_lookups} (
+                    video_id    TEXT    NOT NULL    PRIMARY KEY,
+                    fetched_at  INTEGER NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS {tb_segments} (
+                    video_id    TEXT    NOT NULL,
+                    category    TEXT    NOT NULL,
+                    start       REAL    NOT NULL,
+                    end         REAL    NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS {tb_search_history} (
+                    query       TEXT    NOT NULL    PRIMARY KEY,
+                    searched_at INTEGER NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS {tb_http_cache} (
+                    path        TEXT    NOT NULL    PRIMARY KEY,
+                    body        TEXT    NOT NULL,
+                    fetched_at  INTEGER NOT NULL,
+                    used_at     INTEGER NOT NULL
+                );
            ",
             tb_music = initilize::TB_FAVOURATES_MUSIC,
             tb_playlist = initilize::TB_FAVOURATES_PLAYLIST,
-            tb_artist = initilize::TB_FAVOURATES_ARTIST
+            tb_artist = initilize::TB_FAVOURATES_ARTIST,
+            tb_lookups = initilize::TB_SEGMENT_LOOKUPS,
+            tb_segments = initilize::TB_SKIP_SEGMENTS,
+            tb_http_cache = initilize::TB_HTTP_CACHE,
+            tb_search_history = initilize::TB_SEARCH_HISTORY
         );
 
         let res = connection.execute_batch(&create_favourates_table);
@@ -578,6 +1145,44 @@ impl ConfigContainer {
         Some(config_container)
     }
 
+    // Write the community sources to config file. Only the `Community` section is replaced and
+    // everything else is written back with the values found in the file and not as the config in
+    // use which is validated and modified. See `from_file`. Sections and keys stay in the order
+    // user wrote them as serde_json is built with `preserve_order`
+    pub fn save_community(community: &Community) -> Option<()> {
+        let file_path = Self::get_config_path()?;
+        let content = match std::fs::read_to_string(&file_path) {
+            Ok(val) => val,
+            Err(err) => {
+                eprintln!("Unable to read config file. Error: {err}", err = err);
+                return None;
+            }
+        };
+        let mut config: serde_json::Value = match serde_json::from_str(&content) {
+            Ok(val) => val,
+            Err(err) => {
+                eprintln!(
+                    "Invalid format of config file. Deserialize message: {}",
+                    err
+                );
+                return None;
+            }
+        };
+
+        let community = serde_json::to_value(community).ok()?;
+        match config.as_object_mut() {
+            Some(sections) => {
+                sections.insert("Community".to_string(), community);
+            }
+            None => {
+                eprintln!("Invalid format of config file. Expected an object");
+                return None;
+            }
+        }
+        let content = serde_json::to_string_pretty(&config).ok()?;
+        Self::write_file(&file_path, &content)
+    }
+
     pub fn give_me_config() -> Option<Self> {
         let config_path = ConfigContainer::get_config_path()?;
         let mpv_conf_file = ConfigContainer::get_config_dir()?.join(MPV_OPTION_FILE_NAME);
@@ -646,4 +1251,43 @@ mod tests {
             eprintln!("{:#?}", servers);
         }
     }
+
+    #[test]
+    fn default_audio_filters_are_valid() {
+        let filters = AudioFilters::default();
+        assert_eq!(filters.validate(), Ok(()));
+        assert_eq!(filters.presets[0].to_filter(), None);
+        assert_eq!(
+            filters.presets[1].to_filter().unwrap(),
+            "lavfi=[equalizer=f=60:t=o:w=1:g=6,equalizer=f=170:t=o:w=1:g=4,equalizer=f=310:t=o:w=1:g=2]"
+        );
+    }
+
+    #[test]
+    fn reject_malformed_audio_filters() {
+        let with_filter = |filter: &str| AudioFilters {
+            active: "custom".to_string(),
+            presets: vec![AudioPreset {
+                name: "custom".to_string(),
+                filter: Some(filter.to_string()),
+                bands: Vec::new(),
+            }],
+        };
+
+        assert!(with_filter("lavfi=[bass=g=3,treble=g=2]")
+            .validate()
+            .is_ok());
+        assert!(with_filter("").validate().is_err());
+        assert!(with_filter("bass=g=3,treble=g=2").validate().is_err());
+        assert!(with_filter("lavfi=[bass=g=3").validate().is_err());
+        assert!(with_filter("@label:bass").validate().is_err());
+
+        let mut filters = with_filter("bass=g=3");
+        filters.active = "unknown".to_string();
+        assert!(filters.validate().is_err());
+
+        let mut filters = AudioFilters::default();
+        filters.presets[1].bands[0].gain = 50.0;
+        assert!(filters.validate().is_err());
+    }
 }
@@ -0,0 +1,135 @@
+use config::initilize::{CONFIG, STORAGE, TB_HTTP_CACHE};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Responses of invidious servers stored in local database. Response is keyed by the path of
+// request (endpoint along with the query) so it do not matter which server it came from.
+// See `HttpCache` in config
+
+// Set once from cli before anything is fetched. See `go_offline`
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+// Never send request to server and only serve what is stored, however old it is
+pub fn go_offline() {
+    OFFLINE.store(true, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+// Seconds for which response of this path is fresh. None if it should never be stored
+fn ttl_for(path: &str, cache: &config::HttpCache) -> Option<u64> {
+    if !cache.enabled {
+        None
+    } else if path.starts_with("/trending") {
+        Some(cache.trending_ttl)
+    } else if path.starts_with("/playlists/") {
+        Some(cache.playlist_ttl)
+    } else if path.starts_with("/channels/") {
+        Some(cache.channel_ttl)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn ttl_of(path: &str) -> Option<u64> {
+    ttl_for(path, &CONFIG.http_cache)
+}
+
+// Stored body of this path if it is not older than ttl. Stale one is also returned while offline
+pub(crate) fn read(path: &str, ttl: u64) -> Option<String> {
+    let now = now();
+    let conn = STORAGE.lock().unwrap();
+
+    let (body, fetched_at) = conn
+        .query_row(
+            &format!(
+                "SELECT body, fetched_at FROM {tb_cache} WHERE path = ?1",
+                tb_cache = TB_HTTP_CACHE
+            ),
+            [path],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+        )
+        .ok()?;
+    if now - fetched_at >= ttl as i64 && !is_offline() {
+        return None;
+    }
+
+    // So that it is the last one to be removed
+    conn.execute(
+        &format!(
+            "UPDATE {tb_cache} SET used_at = ?1 WHERE path = ?2",
+            tb_cache = TB_HTTP_CACHE
+        ),
+        rusqlite::params![now, path],
+    )
+    .ok();
+
+    Some(body)
+}
+
+// Store the body and remove least recently used ones if there are more than `max_entries`
+pub(crate) fn store(path: &str, body: &str) {
+    let now = now();
+    let conn = STORAGE.lock().unwrap();
+
+    let res = conn
+        .execute(
+            &format!(
+                "INSERT OR REPLACE INTO {tb_cache} (path, body, fetched_at, used_at) VALUES (?1, ?2, ?3, ?3)",
+                tb_cache = TB_HTTP_CACHE
+            ),
+            rusqlite::params![path, body, now],
+        )
+        .and_then(|_| {
+            conn.execute(
+                &format!(
+                    "DELETE FROM {tb_cache} WHERE path NOT IN (SELECT path FROM {tb_cache} ORDER BY used_at DESC LIMIT ?1)",
+                    tb_cache = TB_HTTP_CACHE
+                ),
+                [CONFIG.http_cache.max_entries as i64],
+            )
+        });
+
+    if let Err(err) = res {
+        eprintln!("Error storing response in cache. Error: {err}", err = err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ttl_by_endpoint() {
+        let cache = config::HttpCache::default();
+        assert_eq!(
+            ttl_for("/trending?type=Music&region=US", &cache),
+            Some(60 * 60)
+        );
+        assert_eq!(
+            ttl_for("/playlists/PL123?page=2&fields=videos", &cache),
+            Some(24 * 60 * 60)
+        );
+        assert_eq!(
+            ttl_for("/channels/UC123/videos?page=1", &cache),
+            Some(6 * 60 * 60)
+        );
+        assert_eq!(ttl_for("/search?q=song&type=music", &cache), None);
+        assert_eq!(ttl_for("/videos/abc", &cache), None);
+
+        let disabled = config::HttpCache {
+            enabled: false,
+            ..Default::default()
+        };
+        assert_eq!(ttl_for("/trending?type=Music", &disabled), None);
+    }
+}
@@ -1,33 +1,63 @@
 use serde::{self, Deserialize, Serialize};
+pub mod cache;
+pub mod lyrics;
 pub mod utils;
+use std::collections::HashMap;
 use std::time::Duration;
 
 pub trait ExtendDuration {
     fn to_string(self) -> String;
-    fn from_string(inp: &str) -> Duration;
+    // Parse the duration formatted as "min:secs" or "hr:min:secs". None if it is not so
+    fn from_string(inp: &str) -> Option<Duration>;
 }
 
-fn num_to_str<'de, D>(input: D) -> Result<String, D::Error>
+// Parse duration as stored in text. Either number of seconds or formatted as "min:secs".
+// Older version of database stored the formatted one
+pub fn parse_duration_text(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    match text.parse::<u64>() {
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        Err(_) => Duration::from_string(text),
+    }
+}
+
+// Parse count as stored in text. Older version of database stored "NaN" when count was unknown
+pub fn parse_count_text(text: &str) -> u64 {
+    text.trim().parse().unwrap_or_default()
+}
+
+// Accepts both number of seconds and duration formatted as string. Live videos have length of 0
+// and liveness is known from `MusicUnit::live` instead
+fn lenient_duration<'de, D>(input: D) -> Result<Duration, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let num: usize = Deserialize::deserialize(input)?;
-    let mut res = num.to_string();
-    res.shrink_to_fit();
-    Ok(res)
+    match serde_json::Value::deserialize(input)? {
+        serde_json::Value::Number(num) => Ok(Duration::from_secs_f64(
+            num.as_f64().unwrap_or_default().max(0.0),
+        )),
+        serde_json::Value::String(text) => Ok(parse_duration_text(&text).unwrap_or_default()),
+        _ => Ok(Duration::default()),
+    }
 }
 
-fn seconds_to_str<'de, D>(input: D) -> Result<String, D::Error>
+fn duration_to_secs<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_u64(duration.as_secs())
+}
+
+// Same as lenient_u64 but keeps the missing or unknown value as None
+fn lenient_opt_u64<'de, D>(input: D) -> Result<Option<u64>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    // Note: If duration is set to 0:0 from the json response
-    // the the video may be live ({islive: true, ..} in response)
-    // to keep things simple ignore all those details and this will simply return "0:0"
-    // this should be documented to inform the user
-    let sec: u64 = Deserialize::deserialize(input)?;
-    let dur: Duration = Duration::from_secs(sec);
-    Ok(dur.to_string())
+    match serde_json::Value::deserialize(input)? {
+        serde_json::Value::Number(num) => Ok(num.as_u64()),
+        serde_json::Value::String(num) => Ok(num.trim().parse().ok()),
+        _ => Ok(None),
+    }
 }
 
 // While fecthing playlist videos from endpoint /playlists/:plid
@@ -38,6 +68,12 @@ struct FetchPlaylistContentRes {
     videos: Vec<MusicUnit>,
 }
 
+// Response of search suggestions is returned as { "query": <query>, "suggestions": [ <String> ] }
+#[derive(Deserialize, Clone, PartialEq)]
+struct FetchSuggestionsRes {
+    suggestions: Vec<String>,
+}
+
 // Serve same purpose as described in struct FetchPlaylistContentRes but
 // to convert to Vec<PlaylistUnit>
 #[derive(Deserialize, Clone, PartialEq)]
@@ -45,8 +81,181 @@ struct FetchArtistPlaylist {
     playlists: Vec<PlaylistUnit>,
 }
 
+// Accepts both number and number formatted as string. Invidious returns some numeric fields
+// like `bitrate` in adaptiveFormats as string
+fn lenient_u64<'de, D>(input: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(input)? {
+        serde_json::Value::Number(num) => Ok(num.as_u64().unwrap_or_default()),
+        serde_json::Value::String(num) => Ok(parse_count_text(&num)),
+        _ => Ok(0),
+    }
+}
+
+// Single stream format in `adaptiveFormats` field of response from /videos/:id
+// Only the fields needed to choose the audio stream are listed
+#[derive(Deserialize, Clone, PartialEq)]
+struct AdaptiveFormat {
+    url: String,
+    // mime type with codec. eg: audio/webm; codecs="opus"
+    #[serde(rename = "type")]
+    mime_type: String,
+    #[serde(default, deserialize_with = "lenient_u64")]
+    bitrate: u64,
+}
+
+// Response of /videos/:id with only the field required to resolve the audio stream
+#[derive(Deserialize, Clone, PartialEq)]
+struct FetchVideoStreamRes {
+    title: String,
+    #[serde(rename = "adaptiveFormats")]
+    adaptive_formats: Vec<AdaptiveFormat>,
+    #[serde(default, rename = "loudnessDb")]
+    loudness_db: Option<f64>,
+}
+
+// Response of /videos/:id when only the loudness is requested
+#[derive(Deserialize, Clone, PartialEq)]
+struct FetchLoudnessRes {
+    #[serde(default, rename = "loudnessDb")]
+    loudness_db: Option<f64>,
+}
+
+// Response of /videos/:id when only the description is requested
+#[derive(Deserialize, Clone, PartialEq)]
+struct FetchDescriptionRes {
+    #[serde(default)]
+    description: String,
+}
+
+// Caption track listed by /captions/:id
+#[derive(Deserialize, Clone)]
+struct CaptionTrack {
+    label: String,
+    #[serde(rename = "languageCode")]
+    language_code: String,
+}
+
+#[derive(Deserialize, Clone)]
+struct FetchCaptionsRes {
+    #[serde(default)]
+    captions: Vec<CaptionTrack>,
+}
+
+// Segment as returned by SponsorBlock api /api/skipSegments
+#[derive(Deserialize, Clone)]
+struct SponsorSegment {
+    category: String,
+    segment: [f64; 2],
+    #[serde(default, rename = "actionType")]
+    action_type: Option<String>,
+}
+
+// Part of video like sponsor read or non-music intro that can be skipped
+#[derive(PartialEq, Clone, Debug)]
+pub struct SkipSegment {
+    pub category: String,
+    pub start: Duration,
+    pub end: Duration,
+}
+
+// Single chapter of a long video like full album or dj set
+#[derive(PartialEq, Clone, Debug)]
+pub struct Chapter {
+    pub title: String,
+    // Time from start of video where this chapter starts
+    pub start: Duration,
+}
+
+// Parse timestamp like 3:45 or 1:02:03. Timestamps in description may be wrapped like [3:45]
+fn parse_timestamp(token: &str) -> Option<Duration> {
+    let token = token.trim_matches(|ch: char| "[]()".contains(ch));
+    let parts = token.split(':').collect::<Vec<_>>();
+    let is_valid = (parts.len() == 2 || parts.len() == 3)
+        && parts.iter().all(|part| {
+            !part.is_empty() && part.len() <= 2 && part.bytes().all(|b| b.is_ascii_digit())
+        })
+        && parts[1..].iter().all(|part| part.len() == 2);
+
+    if is_valid {
+        Duration::from_string(token)
+    } else {
+        None
+    }
+}
+
+// Extract chapters from description of video. Line with a timestamp is a chapter and rest of the
+// line is it's title. eg: "00:00 Intro" or "Intro - 00:00".
+// Same as youtube, it is only considered as chapter list if first chapter starts at 0 and there
+// are at least two chapters in increasing order. Otherwise it is likely just a comment about
+// some moment of video and empty list is returned
+pub fn parse_chapters(description: &str) -> Vec<Chapter> {
+    let mut chapters: Vec<Chapter> = Vec::new();
+    for line in description.lines() {
+        let mut tokens = line.split_whitespace();
+        let start = match tokens.clone().find_map(parse_timestamp) {
+            Some(start) => start,
+            None => continue,
+        };
+
+        let title = tokens
+            .by_ref()
+            .filter(|token| parse_timestamp(token).is_none())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let title = title.trim_matches(|ch: char| ch.is_whitespace() || "-–—|:•".contains(ch));
+
+        if chapters.last().is_some_and(|last| last.start >= start) {
+            return Vec::new();
+        }
+        chapters.push(Chapter {
+            title: title.to_string(),
+            start,
+        });
+    }
+
+    if chapters.len() < 2 || !chapters[0].start.is_zero() {
+        return Vec::new();
+    }
+    chapters
+}
+
+// Channel listed in `relatedChannels` field of response from /channels/:id
+#[derive(Deserialize, Clone, PartialEq)]
+struct RelatedChannel {
+    #[serde(rename = "authorId")]
+    author_id: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct FetchRelatedChannelsRes {
+    #[serde(default, rename = "relatedChannels")]
+    related_channels: Vec<RelatedChannel>,
+}
+
+// Response of /videos/:id when only the related videos are requested
+#[derive(Deserialize, Clone, PartialEq)]
+struct FetchRecommendedRes {
+    #[serde(default, rename = "recommendedVideos")]
+    recommended_videos: Vec<MusicUnit>,
+}
+
+// Direct url of the audio stream of a music unit. This can be given directly to the player
+// so that player do not have to resolve the youtube url itself
+#[derive(PartialEq, Clone, Debug)]
+pub struct AudioStream {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    // Loudness of the track relative to the youtube reference level in dB. Positive value means
+    // track is louder than reference. Not all servers/videos report this
+    pub loudness_db: Option<f64>,
+}
+
 // Represent the single playable music item.
-#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+#[derive(PartialEq, Clone, Default, Serialize, Deserialize, Debug)]
 pub struct MusicUnit {
     // uniquely identifiable id of the youtube channel that represent the publisher of this unit
     // This field exist to make it possible to navigate to the artist channel from the song alone
@@ -59,40 +268,100 @@ pub struct MusicUnit {
     // server return this field as `title`
     #[serde(alias = "title")]
     pub name: String,
-    #[serde(alias = "lengthSeconds")]
-    #[serde(deserialize_with = "seconds_to_str")]
-    pub duration: String,
+    // server return this field as `lengthSeconds`
+    #[serde(default, alias = "lengthSeconds")]
+    #[serde(
+        deserialize_with = "lenient_duration",
+        serialize_with = "duration_to_secs"
+    )]
+    pub duration: Duration,
     #[serde(alias = "videoId")]
     pub id: String,
+    // Live stream has no duration and cannot be seeked
+    // server return this field as `liveNow`
+    #[serde(default, alias = "liveNow")]
+    pub live: bool,
+    // Following are the extra information which may not be known. eg: for music in favourates
+    #[serde(default, alias = "viewCount", deserialize_with = "lenient_opt_u64")]
+    pub views: Option<u64>,
+    // Unix timestamp of when the video was published
+    #[serde(default, deserialize_with = "lenient_opt_u64")]
+    pub published: Option<u64>,
+    #[serde(default, alias = "videoThumbnails")]
+    pub thumbnails: Vec<Thumbnail>,
 }
 
-#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Thumbnail {
+    pub url: String,
+    #[serde(default)]
+    pub width: u32,
+    #[serde(default)]
+    pub height: u32,
+}
+
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ArtistUnit {
     #[serde(alias = "author")]
     pub name: String,
     #[serde(alias = "authorId")]
     pub id: String,
-    #[serde(alias = "videoCount")]
-    #[serde(deserialize_with = "num_to_str")]
-    pub video_count: String,
+    #[serde(default, alias = "videoCount", deserialize_with = "lenient_u64")]
+    pub video_count: u64,
+    #[serde(default, alias = "subCount", deserialize_with = "lenient_opt_u64")]
+    pub subscribers: Option<u64>,
+    #[serde(default, alias = "authorThumbnails")]
+    pub thumbnails: Vec<Thumbnail>,
 }
-#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct PlaylistUnit {
     #[serde(alias = "title")]
     pub name: String,
     #[serde(alias = "playlistId")]
     pub id: String,
     pub author: String,
-    #[serde(alias = "videoCount")]
-    #[serde(deserialize_with = "num_to_str")]
-    pub video_count: String,
+    #[serde(default, alias = "videoCount", deserialize_with = "lenient_u64")]
+    pub video_count: u64,
+    // Url of thumbnail image
+    #[serde(default, alias = "playlistThumbnail")]
+    pub thumbnail: Option<String>,
+}
+
+// Category of trending videos as known by invidious
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum TrendingCategory {
+    Music,
+    Gaming,
+    Movies,
+    // Trending videos of every kind
+    Default,
+}
+
+impl TrendingCategory {
+    pub fn next(self) -> Self {
+        match self {
+            TrendingCategory::Music => TrendingCategory::Gaming,
+            TrendingCategory::Gaming => TrendingCategory::Movies,
+            TrendingCategory::Movies => TrendingCategory::Default,
+            TrendingCategory::Default => TrendingCategory::Music,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TrendingCategory::Music => "music",
+            TrendingCategory::Gaming => "gaming",
+            TrendingCategory::Movies => "movies",
+            TrendingCategory::Default => "videos",
+        }
+    }
 }
 
 #[derive(Default)]
 struct SearchRes {
-    music: Vec<MusicUnit>,
-    playlist: Vec<PlaylistUnit>,
-    artist: Vec<ArtistUnit>,
+    music: ServerPages<MusicUnit>,
+    playlist: ServerPages<PlaylistUnit>,
+    artist: ServerPages<ArtistUnit>,
     query: String,
     last_fetched: i8,
 }
@@ -105,10 +374,98 @@ struct ArtistRes {
 
 #[derive(Default)]
 struct PlaylistRes {
-    music: Vec<MusicUnit>,
+    music: ServerPages<MusicUnit>,
     id: String,
 }
 
+// Result that server gives in pages of it's own size. List asks for items by index and those are
+// served from the pages of server that covers them. Only pages around the last asked ones are kept
+// in memory so that scrolling through huge result do not grow this forever. Size of every page
+// seen is still remembered so that dropped page can be fetched again when list comes back to it
+struct ServerPages<T> {
+    // Number of items in each page of server fetched so far, in order
+    sizes: Vec<usize>,
+    // Items of pages that are kept, keyed by page of server counted from 0
+    content: HashMap<usize, Vec<T>>,
+    // Server has nothing after the last page in `sizes`
+    complete: bool,
+}
+
+impl<T> Default for ServerPages<T> {
+    fn default() -> Self {
+        ServerPages {
+            sizes: Vec::new(),
+            content: HashMap::new(),
+            complete: false,
+        }
+    }
+}
+
+impl<T: Clone> ServerPages<T> {
+    // Pages of server with items in [lower, upper)
+    fn covering(&self, lower: usize, upper: usize) -> std::ops::Range<usize> {
+        let mut first = None;
+        let mut end = 0;
+        let mut offset = 0;
+        for (page, size) in self.sizes.iter().enumerate() {
+            if offset >= upper {
+                break;
+            }
+            if offset + size > lower {
+                first.get_or_insert(page);
+                end = page + 1;
+            }
+            offset += size;
+        }
+        first.unwrap_or(end)..end
+    }
+
+    // Page of server that should be fetched before items in [lower, upper) can be served. None if
+    // those are all here or server do not have that many
+    fn missing(&self, lower: usize, upper: usize) -> Option<usize> {
+        if self.sizes.iter().sum::<usize>() < upper && !self.complete {
+            return Some(self.sizes.len());
+        }
+        self.covering(lower, upper)
+            .find(|page| !self.content.contains_key(page))
+    }
+
+    // Keep the items that server gave for this page
+    fn insert(&mut self, page: usize, items: Vec<T>) {
+        if page == self.sizes.len() {
+            self.sizes.push(items.len());
+        }
+        self.content.insert(page, items);
+    }
+
+    // Server has nothing from this page onwards
+    fn end_at(&mut self, page: usize) {
+        self.complete = true;
+        self.sizes.truncate(page);
+        self.content.retain(|&kept, _| kept < page);
+    }
+
+    // Items in [lower, upper) that are here. Pages other than the ones covering these and their
+    // neighbours are dropped
+    fn take(&mut self, lower: usize, upper: usize) -> Vec<T> {
+        let covering = self.covering(lower, upper);
+        let keep = covering.start.saturating_sub(1)..covering.end + 1;
+        self.content.retain(|page, _| keep.contains(page));
+
+        let mut offset: usize = self.sizes[..covering.start].iter().sum();
+        let mut items = Vec::new();
+        for page in covering {
+            if let Some(content) = self.content.get(&page) {
+                let from = lower.saturating_sub(offset).min(content.len());
+                let to = upper.saturating_sub(offset).min(content.len());
+                items.extend_from_slice(&content[from..to]);
+            }
+            offset += self.sizes[page];
+        }
+        items
+    }
+}
+
 /*
 Return type of the fetch function. This indicates different reason on why no data
 was returned from the fetcher function as stated below.
@@ -133,30 +490,22 @@ pub enum ReturnAction {
 }
 
 pub struct Fetcher {
-    // None if nothing of the trending music is selected.
-    // Stores the vector of music that is trending in music section in specified region
-    // trending_now, is never cleared in a session. Unlike many others data container
-    // below, this field is only appended and read. When user paginate and there are no more
-    // result in container another web request method is made and result is again stored and never cleared.
-    // This may bring little delay when user explore for first time in a session but after that everything
-    // will be in memory making it smooth.
-    trending_now: Option<Vec<MusicUnit>>,
+    // Trending videos of each region and category that is seen in this session. Server gives
+    // the whole list at once so it is fetched only once and then paginated from here.
+    // These are never cleared in a session so switching back to previously seen region or
+    // category is instant. Server only gives few dozens of trending videos so it do not grow much
+    trending: HashMap<(String, TrendingCategory), Vec<MusicUnit>>,
 
     //playlist_content stores collection of music contained in a playlist
     // first field: (String) holds the unique if of playlist that is being read.
-    // All the content in given playlist id is fetched at once and stored meaning
-    // only single web request is needed and no other even when user asks for next page.
-    // On the other hand this means for a playlist with large amount of content
-    // takes more time to initilized.
+    // Server gives the content of playlist in pages (of 100 or so music). Those pages are fetched
+    // only when user explores that far and only the ones around what user is seeing are kept
+    // here. So even a huge playlist shows the first page right away and memory do not grow as
+    // user scrolls through it. Just the different chunks of data is returned.
     // The needed request will return the array of music data. And currently there is no way
     // to fetch only the necessary fields inside music struct. Which means even with playlist
     // of samll size, over data is returend by the data that is just ignored from our side. Thus
     // increasing the network traffic.
-    // On the positive side, it is not needed to send multiple request when user explore the content
-    // in pagiunation. Just the different chunks of data is returned. Also, this makes possible to
-    // feed the player backend with all the content of playlist which means playing from
-    // playlist don't have to get interrupted for fetching more data once played tha last item of
-    // last fetched data. Playback stops only when playlist ends.
     playlist_content: PlaylistRes,
 
     /*
@@ -195,4 +544,102 @@ pub struct Fetcher {
     item_per_page: usize,
     // reference to constants.region in config file
     region: &'static str,
+    // Last response was served from local database instead of server. See cache.rs
+    cache_hit: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_pages_are_bounded() {
+        let mut pages = ServerPages::default();
+        // Server gives 4 items per page and list asks 3 at a time
+        for page in 0..3 {
+            assert_eq!(pages.missing(page * 3, page * 3 + 3), Some(page));
+            pages.insert(page, (page * 4..page * 4 + 4).collect::<Vec<usize>>());
+        }
+        assert_eq!(pages.missing(6, 9), None);
+        assert_eq!(pages.take(6, 9), [6, 7, 8]);
+
+        // Far pages are dropped and fetched again when asked
+        assert_eq!(pages.take(9, 12), [9, 10, 11]);
+        assert!(!pages.content.contains_key(&0));
+        assert_eq!(pages.missing(0, 3), Some(0));
+        pages.insert(0, (0..4).collect());
+        assert_eq!(pages.take(2, 5), [2, 3, 4]);
+
+        // Nothing after last page
+        assert_eq!(pages.missing(12, 15), Some(3));
+        pages.end_at(3);
+        assert_eq!(pages.missing(12, 15), None);
+        assert!(pages.take(12, 15).is_empty());
+    }
+
+    #[test]
+    fn chapters_from_description() {
+        let description = "Tracklist:\n\
+            00:00 Intro\n\
+            [03:45] - Second song\n\
+            Third song | 1:02:03\n\
+            Thanks for listening!";
+
+        let chapters = parse_chapters(description);
+        assert_eq!(
+            chapters,
+            [
+                Chapter {
+                    title: "Intro".to_string(),
+                    start: Duration::from_secs(0)
+                },
+                Chapter {
+                    title: "Second song".to_string(),
+                    start: Duration::from_secs(225)
+                },
+                Chapter {
+                    title: "Third song".to_string(),
+                    start: Duration::from_secs(3723)
+                },
+            ]
+        );
+
+        // Not a chapter list. Just a comment about a moment of video
+        assert!(parse_chapters("Best part at 2:30").is_empty());
+        assert!(parse_chapters("0:00 Start\n5:00 Middle\n3:00 Wrong order").is_empty());
+    }
+
+    #[test]
+    fn typed_fields_from_new_and_old_format() {
+        let music: MusicUnit = serde_json::from_str(
+            r#"{"videoId": "abc", "title": "Song", "author": "Someone", "lengthSeconds": 225,
+                "viewCount": 1200, "published": 1600000000}"#,
+        )
+        .unwrap();
+        assert_eq!(music.duration, Duration::from_secs(225));
+        assert_eq!(music.views, Some(1200));
+        assert!(!music.live);
+
+        // As serialized by older version
+        let old: MusicUnit = serde_json::from_str(
+            r#"{"artist": "Someone", "name": "Song", "duration": "03:45", "id": "abc"}"#,
+        )
+        .unwrap();
+        assert_eq!(old.duration, Duration::from_secs(225));
+        assert_eq!(old.published, None);
+
+        let playlist: PlaylistUnit = serde_json::from_str(
+            r#"{"title": "List", "playlistId": "PL1", "author": "Someone", "videoCount": "12"}"#,
+        )
+        .unwrap();
+        assert_eq!(playlist.video_count, 12);
+
+        assert_eq!(
+            parse_duration_text("1:02:03"),
+            Some(Duration::from_secs(3723))
+        );
+        assert_eq!(parse_duration_text("225"), Some(Duration::from_secs(225)));
+        assert_eq!(parse_duration_text("3:x"), None);
+        assert_eq!(parse_count_text("NaN"), 0);
+    }
 }
@@ -0,0 +1,167 @@
+use std::time::Duration;
+
+// Single line of lyrics or captions
+#[derive(PartialEq, Clone, Debug)]
+pub struct LyricLine {
+    // Time from start of track when this line is sung
+    pub start: Duration,
+    pub text: String,
+}
+
+// Parse the clock time like 01:02:03.456 or 02:03.45 as found in WebVTT and lrc files
+fn parse_clock(clock: &str) -> Option<Duration> {
+    let (whole, fraction) = clock.split_once('.').unwrap_or((clock, ""));
+    let mut secs: u64 = 0;
+    for part in whole.split(':') {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+
+    let fraction = if fraction.is_empty() {
+        0.0
+    } else if fraction.bytes().all(|b| b.is_ascii_digit()) {
+        format!("0.{}", fraction).parse::<f64>().ok()?
+    } else {
+        return None;
+    };
+
+    Some(Duration::from_secs(secs) + Duration::from_secs_f64(fraction))
+}
+
+// Remove the inline tags like <c> or <00:00:01.000> that are used in auto generated captions
+fn strip_tags(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut inside_tag = false;
+    for ch in text.chars() {
+        match ch {
+            '<' => inside_tag = true,
+            '>' if inside_tag => inside_tag = false,
+            _ if !inside_tag => result.push(ch),
+            _ => {}
+        }
+    }
+    result
+}
+
+// Parse WebVTT as returned by /captions/:id. Each cue becomes a line.
+// Auto generated captions repeat the previous cue while scrolling so consecutive duplicates are
+// dropped
+pub fn parse_webvtt(content: &str) -> Vec<LyricLine> {
+    let mut lines: Vec<LyricLine> = Vec::new();
+    let mut cue_lines = content.lines();
+
+    while let Some(line) = cue_lines.next() {
+        let start = match line.split_once("-->") {
+            Some((start, _)) => match parse_clock(start.trim()) {
+                Some(start) => start,
+                None => continue,
+            },
+            None => continue,
+        };
+
+        // Text of cue continues until blank line
+        let text = cue_lines
+            .by_ref()
+            .take_while(|line| !line.trim().is_empty())
+            .map(|line| strip_tags(line).trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+
+        for text in text {
+            if lines.iter().rev().take(2).any(|prev| prev.text == text) {
+                continue;
+            }
+            lines.push(LyricLine { start, text });
+        }
+    }
+
+    lines
+}
+
+// Parse lyrics in lrc format. eg: "[01:02.30]Some line". Single line may have multiple timestamps
+// when it is repeated. Metadata tags like [ar:Artist] are ignored
+pub fn parse_lrc(content: &str) -> Vec<LyricLine> {
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        let mut rest = line.trim();
+        let mut starts = Vec::new();
+        while let Some(tag) = rest.strip_prefix('[') {
+            let (clock, remaining) = match tag.split_once(']') {
+                Some(split) => split,
+                None => break,
+            };
+            match parse_clock(clock) {
+                Some(start) => starts.push(start),
+                None => break,
+            }
+            rest = remaining;
+        }
+
+        for start in starts {
+            lines.push(LyricLine {
+                start,
+                text: rest.trim().to_string(),
+            });
+        }
+    }
+
+    lines.sort_by_key(|line| line.start);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(millis: u64, text: &str) -> LyricLine {
+        LyricLine {
+            start: Duration::from_millis(millis),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn webvtt_cues() {
+        let content = "WEBVTT\n\
+            Kind: captions\n\
+            Language: en\n\
+            \n\
+            00:00:01.500 --> 00:00:04.000 align:start position:0%\n\
+            first <c>line</c>\n\
+            \n\
+            00:00:04.000 --> 00:00:06.000\n\
+            first line\n\
+            second line\n\
+            \n\
+            01:02.250 --> 01:03.000\n\
+            <00:01:02.250>third\n";
+
+        assert_eq!(
+            parse_webvtt(content),
+            [
+                line(1500, "first line"),
+                line(4000, "second line"),
+                line(62250, "third"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lrc_lines() {
+        let content = "[ar:Someone]\n\
+            [00:12.50]Hello\n\
+            [00:05.00][00:30.00]Chorus\n\
+            not a lyric line";
+
+        assert_eq!(
+            parse_lrc(content),
+            [
+                line(5000, "Chorus"),
+                line(12500, "Hello"),
+                line(30000, "Chorus"),
+            ]
+        );
+    }
+}
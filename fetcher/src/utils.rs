@@ -1,18 +1,27 @@
 use crate::{Fetcher, ReturnAction};
 use config::initilize::{
     CONFIG, STORAGE, TB_FAVOURATES_ARTIST, TB_FAVOURATES_MUSIC, TB_FAVOURATES_PLAYLIST,
+    TB_SEGMENT_LOOKUPS, TB_SKIP_SEGMENTS,
 };
 use reqwest;
+use rusqlite;
+use std::collections::{HashMap, HashSet};
 use std::iter::DoubleEndedIterator;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/92.0.4515.131 Safari/537.36";
 const FIELDS: [&str; 3] = [
-    "videoId,title,author,lengthSeconds",
-    "title,playlistId,author,videoCount",
-    "author,authorId,videoCount",
+    "videoId,title,author,lengthSeconds,liveNow,viewCount,published,videoThumbnails(url,width,height)",
+    "title,playlistId,author,videoCount,playlistThumbnail",
+    "author,authorId,videoCount,subCount,authorThumbnails(url,width,height)",
 ];
 const FILTER_TYPE: [&str; 3] = ["music", "playlist", "channel"];
+// Artist mix is made from uploads of the artist and these many of it's playlists and related
+// channels. At most MIX_PER_SOURCE music is taken from each of those so that no single source
+// fills the whole mix
+const MIX_PLAYLIST_COUNT: usize = 3;
+const MIX_RELATED_CHANNEL_COUNT: usize = 3;
+const MIX_PER_SOURCE: usize = 20;
 
 impl crate::ExtendDuration for Duration {
     fn to_string(self) -> String {
@@ -33,38 +42,31 @@ impl crate::ExtendDuration for Duration {
         }
     }
 
-    // This function assumes that the string is alwayd formatted in "min:secs"
-    fn from_string(inp: &str) -> Duration {
+    fn from_string(inp: &str) -> Option<Duration> {
         let mut time_components = inp.split(':');
 
-        let seconds = time_components
-            .next_back()
-            .unwrap_or("0")
-            .trim()
-            .parse::<u64>()
-            .unwrap();
-        let minutes = time_components
-            .next_back()
-            .unwrap_or("0")
-            .trim()
-            .parse::<u64>()
-            .unwrap();
-        let hours = time_components
-            .next_back()
-            .unwrap_or("0")
-            .trim()
-            .parse::<u64>()
-            .unwrap();
+        let seconds = time_components.next_back()?.trim().parse::<u64>().ok()?;
+        let minutes = match time_components.next_back() {
+            Some(minutes) => minutes.trim().parse::<u64>().ok()?,
+            None => 0,
+        };
+        let hours = match time_components.next_back() {
+            Some(hours) => hours.trim().parse::<u64>().ok()?,
+            None => 0,
+        };
+        if time_components.next().is_some() {
+            return None;
+        }
 
         let total_secs = seconds + (minutes * 60) + (hours * 60 * 60);
-        Duration::from_secs(total_secs)
+        Some(Duration::from_secs(total_secs))
     }
 }
 
 impl Default for Fetcher {
     fn default() -> Self {
         super::Fetcher {
-            trending_now: None,
+            trending: HashMap::new(),
             playlist_content: super::PlaylistRes::default(),
             artist_content: super::ArtistRes::default(),
             search_res: super::SearchRes::default(),
@@ -77,6 +79,7 @@ impl Default for Fetcher {
             active_server_index: 0,
             region: &CONFIG.constants.region,
             item_per_page: CONFIG.constants.item_per_list,
+            cache_hit: false,
         }
     }
 }
@@ -117,44 +120,38 @@ macro_rules! search {
     };
 
     ("@internal-core", $fetcher: expr, $query: expr, $page: expr, $store_target: expr, $filter_index: expr, $unit_type: ty) => {{
-        let suffix = format!(
-            "/search?q={query}&type={s_type}&{region}&page={page}&fields={fields}",
-            query = $query,
-            s_type = FILTER_TYPE[$filter_index],
-            region = $fetcher.region,
-            fields = FIELDS[$filter_index],
-            page = $page
-        );
         let lower_limit = $page * $fetcher.item_per_page;
-        let mut upper_limit =
-            std::cmp::min($store_target.len(), lower_limit + $fetcher.item_per_page);
+        let upper_limit = lower_limit + $fetcher.item_per_page;
 
         let is_new_query = *$query != $fetcher.search_res.query;
         let is_new_type = $fetcher.search_res.last_fetched != $filter_index;
-        let insufficient_data =
-            upper_limit.checked_sub(lower_limit).unwrap_or(0) < $fetcher.item_per_page;
-
         $fetcher.search_res.last_fetched = $filter_index;
-        if is_new_query || insufficient_data || is_new_type {
-            let obj = $fetcher.send_request::<Vec<$unit_type>>(&suffix, 1).await;
-            if is_new_query || is_new_type {
-                $store_target.clear();
-            }
-            match obj {
-                Ok(data) => {
-                    $fetcher.search_res.query = $query.to_string();
-                    $store_target.extend_from_slice(data.as_slice());
-                    upper_limit =
-                        std::cmp::min($store_target.len(), lower_limit + $fetcher.item_per_page);
-                }
+        if is_new_query || is_new_type {
+            $store_target = Default::default();
+            $fetcher.search_res.query = $query.to_string();
+        }
+
+        while let Some(server_page) = $store_target.missing(lower_limit, upper_limit) {
+            let suffix = format!(
+                "/search?q={query}&type={s_type}&{region}&page={page}&fields={fields}",
+                query = $query,
+                s_type = FILTER_TYPE[$filter_index],
+                region = $fetcher.region,
+                fields = FIELDS[$filter_index],
+                page = server_page + 1
+            );
+            match $fetcher.send_request::<Vec<$unit_type>>(&suffix, 1).await {
+                Ok(data) if data.is_empty() => $store_target.end_at(server_page),
+                Ok(data) => $store_target.insert(server_page, data),
                 Err(e) => return Err(e),
             }
         }
 
-        if upper_limit > lower_limit {
-            Ok($store_target[lower_limit..upper_limit].to_vec())
-        } else {
+        let res = $store_target.take(lower_limit, upper_limit);
+        if res.is_empty() {
             Err(ReturnAction::EOR)
+        } else {
+            Ok(res)
         }
     }};
 }
@@ -164,7 +161,15 @@ impl Fetcher {
         self.active_server_index = (self.active_server_index + 1) % self.servers.len();
     }
 
+    // Tells if the last response was served from local database. Flag is cleared on reading so
+    // that response served from memory later is not reported as cached
+    pub fn take_cache_hit(&mut self) -> bool {
+        std::mem::take(&mut self.cache_hit)
+    }
+
     // All the request should be send from this function
+    // Response of endpoint that is cached is served from local database while it is fresh. See
+    // cache.rs
     async fn send_request<'de, Res>(
         &mut self,
         path: &str,
@@ -173,6 +178,19 @@ impl Fetcher {
     where
         Res: serde::de::DeserializeOwned,
     {
+        let ttl = crate::cache::ttl_of(path);
+        if let Some(body) = ttl.and_then(|ttl| crate::cache::read(path, ttl)) {
+            // Stored response that no longer parses (eg: fields changed) is just fetched again
+            if let Ok(obj) = serde_json::from_str::<Res>(&body) {
+                self.cache_hit = true;
+                return Ok(obj);
+            }
+        }
+        self.cache_hit = false;
+        if crate::cache::is_offline() {
+            return Err(ReturnAction::Failed);
+        }
+
         self.change_server();
 
         let url = self.servers[self.active_server_index].to_string() + path;
@@ -180,10 +198,15 @@ impl Fetcher {
 
         match res {
             Ok(response) => {
-                if let Ok(obj) = response.json::<Res>().await {
-                    Ok(obj)
-                } else {
-                    Err(ReturnAction::Failed)
+                let body = response.text().await.map_err(|_| ReturnAction::Failed)?;
+                match serde_json::from_str::<Res>(&body) {
+                    Ok(obj) => {
+                        if ttl.is_some() {
+                            crate::cache::store(path, &body);
+                        }
+                        Ok(obj)
+                    }
+                    Err(_) => Err(ReturnAction::Failed),
                 }
             }
             Err(_) if retry_for > 0 => {
@@ -194,36 +217,66 @@ impl Fetcher {
         }
     }
 
-    pub async fn get_trending_music(
+    // Same as send_request but the body is returned as it is instead of parsing it as json
+    async fn send_text_request(
+        &mut self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<String, ReturnAction> {
+        if crate::cache::is_offline() {
+            return Err(ReturnAction::Failed);
+        }
+        self.change_server();
+
+        let url = self.servers[self.active_server_index].to_string() + path;
+        let res = self.client.get(url).query(query).send().await;
+
+        match res {
+            Ok(response) if response.status().is_success() => {
+                response.text().await.map_err(|_| ReturnAction::Failed)
+            }
+            _ => Err(ReturnAction::Failed),
+        }
+    }
+
+    pub async fn get_trending(
         &mut self,
+        region: &str,
+        category: super::TrendingCategory,
         page: usize,
     ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
         let lower_limit = self.item_per_page * page;
+        let key = (region.to_string(), category);
 
-        if self.trending_now.is_none() {
+        if !self.trending.contains_key(&key) {
+            // Invidious gives trending of every kind when type is not given
+            let category_param = match category {
+                super::TrendingCategory::Music => "type=Music&",
+                super::TrendingCategory::Gaming => "type=Gaming&",
+                super::TrendingCategory::Movies => "type=Movies&",
+                super::TrendingCategory::Default => "",
+            };
             let suffix = format!(
-                "/trending?type=Music&region={region}&fields={music_field}",
-                region = self.region,
+                "/trending?{category}region={region}&fields={music_field}",
+                category = category_param,
+                region = region,
                 music_field = FIELDS[0]
             );
 
-            let obj = self.send_request::<Vec<super::MusicUnit>>(&suffix, 2).await;
-            match obj {
-                Ok(mut res) => {
-                    res.shrink_to_fit();
-                    self.trending_now = Some(res);
-                }
-                Err(e) => return Err(e),
-            }
+            let mut res = self
+                .send_request::<Vec<super::MusicUnit>>(&suffix, 2)
+                .await?;
+            res.shrink_to_fit();
+            self.trending.insert(key.clone(), res);
         }
 
-        let trending_now = self.trending_now.as_ref().unwrap();
-        let upper_limit = std::cmp::min(trending_now.len(), lower_limit + self.item_per_page);
+        let trending = &self.trending[&key];
+        let upper_limit = std::cmp::min(trending.len(), lower_limit + self.item_per_page);
 
         if lower_limit >= upper_limit {
             Err(ReturnAction::EOR)
         } else {
-            Ok(trending_now[lower_limit..upper_limit].to_vec())
+            Ok(trending[lower_limit..upper_limit].to_vec())
         }
     }
 
@@ -234,40 +287,61 @@ impl Fetcher {
     ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
         let lower_limit = page * self.item_per_page;
 
-        let is_new_id = *playlist_id != self.playlist_content.id;
-        if is_new_id {
-            self.playlist_content.id = playlist_id.to_string();
-            let suffix = format!(
-                "/playlists/{playlist_id}?fields=videos({music_field})",
-                playlist_id = playlist_id,
-                music_field = FIELDS[0]
-            );
+        if *playlist_id != self.playlist_content.id {
+            self.playlist_content = super::PlaylistRes {
+                id: playlist_id.to_string(),
+                ..Default::default()
+            };
+        }
 
-            let obj = self
-                .send_request::<super::FetchPlaylistContentRes>(&suffix, 1)
-                .await;
-            match obj {
-                Ok(mut data) => {
-                    data.videos.shrink_to_fit();
-                    self.playlist_content.music = data.videos;
-                }
+        // Fetch the pages from server until there is enough for this page. Content is only
+        // changed once a page is fetched so cancelling in between leaves nothing half done
+        let upper_limit = lower_limit + self.item_per_page;
+        while let Some(server_page) = self
+            .playlist_content
+            .music
+            .missing(lower_limit, upper_limit)
+        {
+            match self.get_playlist_page(playlist_id, server_page).await {
+                Ok(music) => self.playlist_content.music.insert(server_page, music),
+                Err(ReturnAction::EOR) => self.playlist_content.music.end_at(server_page),
                 Err(e) => return Err(e),
             }
         }
 
-        let upper_limit = std::cmp::min(
-            self.playlist_content.music.len(),
-            lower_limit + self.item_per_page,
-        );
-        if lower_limit >= upper_limit {
+        let res = self.playlist_content.music.take(lower_limit, upper_limit);
+        if res.is_empty() {
             Err(ReturnAction::EOR)
         } else {
-            let mut res = self.playlist_content.music[lower_limit..upper_limit].to_vec();
-            res.shrink_to_fit();
             Ok(res)
         }
     }
 
+    // Single page of playlist as served by the server. Page is counted from 0 here. Size of page is
+    // decided by server. Returns EOR once there is nothing left in the playlist
+    pub async fn get_playlist_page(
+        &mut self,
+        playlist_id: &str,
+        page: usize,
+    ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
+        let suffix = format!(
+            "/playlists/{playlist_id}?page={page}&fields=videos({music_field})",
+            playlist_id = playlist_id,
+            page = page + 1,
+            music_field = FIELDS[0]
+        );
+
+        let mut data = self
+            .send_request::<super::FetchPlaylistContentRes>(&suffix, 1)
+            .await?;
+        if data.videos.is_empty() {
+            Err(ReturnAction::EOR)
+        } else {
+            data.videos.shrink_to_fit();
+            Ok(data.videos)
+        }
+    }
+
     pub async fn get_playlist_of_channel(
         &mut self,
         channel_id: &str,
@@ -277,7 +351,6 @@ impl Fetcher {
 
         let is_new_id = *channel_id != self.artist_content.playlist.0;
         if is_new_id || self.artist_content.playlist.1.is_empty() {
-            self.artist_content.playlist.0 = channel_id.to_string();
             let suffix = format!(
                 "/channels/{channel_id}/playlists?fields=playlists({channel_fields})",
                 channel_id = channel_id,
@@ -290,6 +363,7 @@ impl Fetcher {
             match obj {
                 Ok(mut data) => {
                     data.playlists.shrink_to_fit();
+                    self.artist_content.playlist.0 = channel_id.to_string();
                     self.artist_content.playlist.1 = data.playlists;
                 }
                 Err(e) => return Err(e),
@@ -318,21 +392,10 @@ impl Fetcher {
 
         let is_new_id = *channel_id != self.artist_content.music.0;
         if is_new_id || self.artist_content.music.1.is_empty() {
+            let mut data = self.fetch_channel_videos(channel_id).await?;
+            data.shrink_to_fit();
             self.artist_content.music.0 = channel_id.to_string();
-            let suffix = format!(
-                "/channels/{channel_id}/videos&fields={music_field}",
-                channel_id = channel_id,
-                music_field = FIELDS[0]
-            );
-
-            let obj = self.send_request::<Vec<super::MusicUnit>>(&suffix, 1).await;
-            match obj {
-                Ok(mut data) => {
-                    data.shrink_to_fit();
-                    self.artist_content.music.1 = data;
-                }
-                Err(e) => return Err(e),
-            }
+            self.artist_content.music.1 = data;
         }
 
         let upper_limit = std::cmp::min(
@@ -348,6 +411,306 @@ impl Fetcher {
         }
     }
 
+    async fn fetch_channel_videos(
+        &mut self,
+        channel_id: &str,
+    ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
+        let suffix = format!(
+            "/channels/{channel_id}/videos?fields={music_field}",
+            channel_id = channel_id,
+            music_field = FIELDS[0]
+        );
+
+        self.send_request::<Vec<super::MusicUnit>>(&suffix, 1).await
+    }
+
+    // Collect the music for artist radio from uploads of the channel, content of it's playlists
+    // and uploads of related channels. Duplicates, live streams and music longer than
+    // `Playback::mix_max_secs` are skipped.
+    // Every source is added if it can be fetched. Fails only if none of them could be
+    pub async fn get_artist_mix(
+        &mut self,
+        channel_id: &str,
+    ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
+        let mut sources = Vec::new();
+        if let Ok(videos) = self.fetch_channel_videos(channel_id).await {
+            sources.push(videos);
+        }
+
+        let suffix = format!(
+            "/channels/{channel_id}/playlists?fields=playlists({playlist_fields})",
+            channel_id = channel_id,
+            playlist_fields = FIELDS[1],
+        );
+        if let Ok(res) = self
+            .send_request::<super::FetchArtistPlaylist>(&suffix, 1)
+            .await
+        {
+            for playlist in res.playlists.iter().take(MIX_PLAYLIST_COUNT) {
+                let suffix = format!(
+                    "/playlists/{playlist_id}?fields=videos({music_field})",
+                    playlist_id = playlist.id,
+                    music_field = FIELDS[0]
+                );
+                if let Ok(content) = self
+                    .send_request::<super::FetchPlaylistContentRes>(&suffix, 1)
+                    .await
+                {
+                    sources.push(content.videos);
+                }
+            }
+        }
+
+        let suffix = format!(
+            "/channels/{channel_id}?fields=relatedChannels(authorId)",
+            channel_id = channel_id
+        );
+        if let Ok(res) = self
+            .send_request::<super::FetchRelatedChannelsRes>(&suffix, 1)
+            .await
+        {
+            for channel in res.related_channels.iter().take(MIX_RELATED_CHANNEL_COUNT) {
+                if let Ok(videos) = self.fetch_channel_videos(&channel.author_id).await {
+                    sources.push(videos);
+                }
+            }
+        }
+
+        if sources.is_empty() {
+            return Err(ReturnAction::Failed);
+        }
+        let mut seen = HashSet::new();
+        let mix = sources
+            .into_iter()
+            .flat_map(|source| {
+                source
+                    .into_iter()
+                    .filter(|music| {
+                        let secs = music.duration.as_secs();
+                        secs > 0 && secs <= CONFIG.playback.mix_max_secs
+                    })
+                    .take(MIX_PER_SOURCE)
+            })
+            .filter(|music| seen.insert(music.id.clone()))
+            .collect::<Vec<_>>();
+
+        if mix.is_empty() {
+            Err(ReturnAction::EOR)
+        } else {
+            Ok(mix)
+        }
+    }
+
+    // Resolve the direct url of best quality audio stream of given video.
+    // This is same thing player does before playing youtube url but done ahead of time.
+    // Stream is asked to be proxied through the server (local=true) as googlevideo url is only
+    // valid for the ip that resolved it, which is the server and not us
+    pub async fn get_audio_stream(
+        &mut self,
+        video_id: &str,
+    ) -> Result<super::AudioStream, ReturnAction> {
+        let suffix = format!(
+            "/videos/{video_id}?local=true&fields=title,loudnessDb,adaptiveFormats(url,type,bitrate)",
+            video_id = video_id
+        );
+
+        let res = self
+            .send_request::<super::FetchVideoStreamRes>(&suffix, 1)
+            .await?;
+        // Proxied url is a path in the server that answered the request
+        let server = reqwest::Url::parse(&self.servers[self.active_server_index])
+            .map_err(|_| ReturnAction::Failed)?;
+
+        let best_audio = res
+            .adaptive_formats
+            .into_iter()
+            .filter(|format| format.mime_type.starts_with("audio/"))
+            .max_by_key(|format| format.bitrate);
+
+        match best_audio {
+            Some(format) => Ok(super::AudioStream {
+                id: video_id.to_string(),
+                title: res.title,
+                url: server
+                    .join(&format.url)
+                    .map_err(|_| ReturnAction::Failed)?
+                    .to_string(),
+                loudness_db: res.loudness_db,
+            }),
+            // Usually this is the case for live streams which only have hls manifest
+            None => Err(ReturnAction::Failed),
+        }
+    }
+
+    // Get the chapters of video from timestamps in it's description
+    pub async fn get_chapters(
+        &mut self,
+        video_id: &str,
+    ) -> Result<Vec<super::Chapter>, ReturnAction> {
+        let suffix = format!("/videos/{video_id}?fields=description", video_id = video_id);
+
+        self.send_request::<super::FetchDescriptionRes>(&suffix, 1)
+            .await
+            .map(|res| super::parse_chapters(&res.description))
+    }
+
+    // Get the captions of video as timed lines. First available language from `languages` is
+    // preferred and first caption track is used if none of them is available
+    pub async fn get_captions(
+        &mut self,
+        video_id: &str,
+        languages: &[String],
+    ) -> Result<Vec<crate::lyrics::LyricLine>, ReturnAction> {
+        let path = format!("/captions/{video_id}", video_id = video_id);
+        let list = self
+            .send_request::<super::FetchCaptionsRes>(&format!("{}?fields=captions", path), 1)
+            .await?;
+
+        let track = languages
+            .iter()
+            .find_map(|lang| {
+                list.captions
+                    .iter()
+                    .find(|track| track.language_code == *lang)
+            })
+            .or_else(|| list.captions.first())
+            .ok_or(ReturnAction::EOR)?;
+
+        let content = self
+            .send_text_request(&path, &[("label", &track.label)])
+            .await?;
+        let lines = crate::lyrics::parse_webvtt(&content);
+        if lines.is_empty() {
+            Err(ReturnAction::EOR)
+        } else {
+            Ok(lines)
+        }
+    }
+
+    // Get the segments of video that should be skipped as per `SegmentSkip::skip` in config.
+    // Segments stored in local database are used while they are fresh. Otherwise those are
+    // fetched from api and stored again. If api cannot be reached, stale ones are used anyway
+    pub async fn get_skip_segments(
+        &mut self,
+        video_id: &str,
+    ) -> Result<Vec<super::SkipSegment>, ReturnAction> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let max_age = (CONFIG.segment_skip.cache_days * 24 * 60 * 60) as i64;
+        let wanted = |segments: Vec<super::SkipSegment>| {
+            segments
+                .into_iter()
+                .filter(|segment| CONFIG.segment_skip.skip.contains(&segment.category))
+                .collect::<Vec<_>>()
+        };
+
+        let cached = read_cached_segments(video_id);
+        if let Some((fetched_at, segments)) = &cached {
+            if now - fetched_at < max_age {
+                return Ok(wanted(segments.clone()));
+            }
+        }
+
+        match self.fetch_skip_segments(video_id).await {
+            Ok(segments) => {
+                store_segments(video_id, &segments, now);
+                Ok(wanted(segments))
+            }
+            Err(err) => match cached {
+                Some((_, segments)) => Ok(wanted(segments)),
+                None => Err(err),
+            },
+        }
+    }
+
+    // Segments of all the categories are fetched so that those in local database can still be
+    // used when categories to skip are changed
+    async fn fetch_skip_segments(
+        &mut self,
+        video_id: &str,
+    ) -> Result<Vec<super::SkipSegment>, ReturnAction> {
+        // Stored segments are used anyway. See `get_skip_segments`
+        if crate::cache::is_offline() {
+            return Err(ReturnAction::Failed);
+        }
+        let url = format!(
+            "{api}/api/skipSegments",
+            api = CONFIG.segment_skip.api.trim_end_matches('/')
+        );
+        let categories = format!(
+            "[{}]",
+            config::SEGMENT_CATEGORIES
+                .iter()
+                .map(|category| format!("\"{}\"", category))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+
+        let response = self
+            .client
+            .get(url)
+            .query(&[("videoID", video_id), ("categories", &categories)])
+            .send()
+            .await
+            .map_err(|_| ReturnAction::Failed)?;
+
+        // Api responds with not found when video has no segments
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        if !response.status().is_success() {
+            return Err(ReturnAction::Failed);
+        }
+
+        let segments = response
+            .json::<Vec<super::SponsorSegment>>()
+            .await
+            .map_err(|_| ReturnAction::Failed)?;
+        Ok(segments
+            .into_iter()
+            .filter(|segment| segment.action_type.as_deref().unwrap_or("skip") == "skip")
+            .filter(|segment| segment.segment[0] >= 0.0 && segment.segment[1] > segment.segment[0])
+            .map(|segment| super::SkipSegment {
+                category: segment.category,
+                start: Duration::from_secs_f64(segment.segment[0]),
+                end: Duration::from_secs_f64(segment.segment[1]),
+            })
+            .collect())
+    }
+
+    // Get the loudness of video as reported by youtube. Ok(None) means server do not know it
+    pub async fn get_loudness(&mut self, video_id: &str) -> Result<Option<f64>, ReturnAction> {
+        let suffix = format!("/videos/{video_id}?fields=loudnessDb", video_id = video_id);
+
+        self.send_request::<super::FetchLoudnessRes>(&suffix, 1)
+            .await
+            .map(|res| res.loudness_db)
+    }
+
+    // Get the videos that youtube recommends along with given video. Used to keep playing
+    // something similar when queue ends
+    pub async fn get_recommended(
+        &mut self,
+        video_id: &str,
+    ) -> Result<Vec<super::MusicUnit>, ReturnAction> {
+        let suffix = format!(
+            "/videos/{video_id}?fields=recommendedVideos({music_field})",
+            video_id = video_id,
+            music_field = FIELDS[0]
+        );
+
+        let res = self
+            .send_request::<super::FetchRecommendedRes>(&suffix, 1)
+            .await?;
+        if res.recommended_videos.is_empty() {
+            Err(ReturnAction::EOR)
+        } else {
+            Ok(res.recommended_videos)
+        }
+    }
+
     pub async fn get_favourates_music(
         &mut self,
         page: usize,
@@ -383,7 +746,9 @@ impl Fetcher {
                 id: row.get(0).unwrap_or_default(),
                 name: row.get(1).unwrap_or("SQL_ERROR".into()),
                 artist: row.get(2).unwrap_or("SQL_ERROR".into()),
-                duration: row.get(3).unwrap_or("3:0".into()),
+                duration: crate::parse_duration_text(&row.get::<_, String>(3).unwrap_or_default())
+                    .unwrap_or_default(),
+                ..Default::default()
             })
         });
 
@@ -447,7 +812,8 @@ impl Fetcher {
                 id: row.get(0).unwrap_or_default(),
                 name: row.get(1).unwrap_or("SQL_ERROR".into()),
                 author: row.get(2).unwrap_or("SQL_ERROR".into()),
-                video_count: row.get(3).unwrap_or("NaN".into()),
+                video_count: crate::parse_count_text(&row.get::<_, String>(3).unwrap_or_default()),
+                ..Default::default()
             })
         });
 
@@ -511,7 +877,8 @@ impl Fetcher {
             Ok(super::ArtistUnit {
                 id: row.get(0).unwrap_or_default(),
                 name: row.get(1).unwrap_or("SQL_ERROR".into()),
-                video_count: row.get(2).unwrap_or("NaN".into()),
+                video_count: crate::parse_count_text(&row.get::<_, String>(2).unwrap_or_default()),
+                ..Default::default()
             })
         });
 
@@ -556,6 +923,21 @@ impl Fetcher {
         search!("playlist", self, query, page)
     }
 
+    // Queries suggested by server for what is being typed in search box
+    pub async fn get_search_suggestions(
+        &mut self,
+        query: &str,
+    ) -> Result<Vec<String>, ReturnAction> {
+        let suffix = format!(
+            "/search/suggestions?{query}",
+            query = encode_query(&[("q", query)])
+        );
+        let res = self
+            .send_request::<super::FetchSuggestionsRes>(&suffix, 1)
+            .await?;
+        Ok(res.suggestions)
+    }
+
     pub async fn search_artist(
         &mut self,
         query: &str,
@@ -564,3 +946,95 @@ impl Fetcher {
         search!("artist", self, query, page)
     }
 }
+
+// Query string of given pairs with values percent encoded. eg: [("q", "rock & roll")] gives
+// "q=rock+%26+roll"
+fn encode_query(pairs: &[(&str, &str)]) -> String {
+    let mut url = reqwest::Url::parse("http://localhost/").unwrap();
+    url.query_pairs_mut().extend_pairs(pairs);
+    url.query().unwrap_or_default().to_string()
+}
+
+// Returns the time segments were fetched at along with the segments. None if never fetched
+fn read_cached_segments(video_id: &str) -> Option<(i64, Vec<super::SkipSegment>)> {
+    let conn = STORAGE.lock().unwrap();
+
+    let fetched_at = conn
+        .query_row(
+            &format!(
+                "SELECT fetched_at FROM {tb_lookups} WHERE video_id = ?1",
+                tb_lookups = TB_SEGMENT_LOOKUPS
+            ),
+            [video_id],
+            |row| row.get::<_, i64>(0),
+        )
+        .ok()?;
+
+    let query = format!(
+        "SELECT category, start, end FROM {tb_segments} WHERE video_id = ?1 ORDER BY start",
+        tb_segments = TB_SKIP_SEGMENTS
+    );
+    let mut stmt = match conn.prepare(&query) {
+        Ok(val) => val,
+        Err(err) => {
+            eprintln!(
+                "Error preparing select statement for skip segments. Error: {err}",
+                err = err
+            );
+            return None;
+        }
+    };
+    let segments = stmt
+        .query_map([video_id], |row| {
+            Ok(super::SkipSegment {
+                category: row.get(0)?,
+                start: Duration::from_secs_f64(row.get::<_, f64>(1)?.max(0.0)),
+                end: Duration::from_secs_f64(row.get::<_, f64>(2)?.max(0.0)),
+            })
+        })
+        .ok()?
+        .filter_map(Result::ok)
+        .collect();
+
+    Some((fetched_at, segments))
+}
+
+fn store_segments(video_id: &str, segments: &[super::SkipSegment], fetched_at: i64) {
+    let mut conn = STORAGE.lock().unwrap();
+
+    let res = conn.transaction().and_then(|tx| {
+        tx.execute(
+            &format!(
+                "DELETE FROM {tb_segments} WHERE video_id = ?1",
+                tb_segments = TB_SKIP_SEGMENTS
+            ),
+            [video_id],
+        )?;
+        for segment in segments {
+            tx.execute(
+                &format!(
+                    "INSERT INTO {tb_segments} (video_id, category, start, end) VALUES (?1, ?2, ?3, ?4)",
+                    tb_segments = TB_SKIP_SEGMENTS
+                ),
+                rusqlite::params![
+                    video_id,
+                    segment.category,
+                    segment.start.as_secs_f64(),
+                    segment.end.as_secs_f64()
+                ],
+            )?;
+        }
+        tx.execute(
+            &format!(
+                "INSERT OR REPLACE INTO {tb_lookups} (video_id, fetched_at) VALUES (?1, ?2)",
+                tb_lookups = TB_SEGMENT_LOOKUPS
+            ),
+            rusqlite::params![video_id, fetched_at],
+        )?;
+        tx.commit()
+    });
+
+    if let Err(err) = res {
+        eprintln!("Error storing skip segments. Error: {err}", err = err);
+    }
+}
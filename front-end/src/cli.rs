@@ -58,6 +58,9 @@ __   ___         _                           _
         match self.sub_command.trim() {
             "run" => {
                 self.initialize_globals();
+                if self.arguments.iter().any(|arg| arg == "--offline") {
+                    fetcher::cache::go_offline();
+                }
                 should_continue = true;
             }
             "update" => self.update(),
@@ -115,9 +118,22 @@ __   ___         _                           _
             prev = keys.prev,
             suf = keys.suffle,
             rep = keys.repeat,
+            auto = keys.autoplay,
+            radio = keys.artist_radio,
+            chap = keys.chapters,
+            n_chap = keys.next_chapter,
+            p_chap = keys.prev_chapter,
+            lyr = keys.lyrics,
+            vid = keys.video,
+            loud = keys.loudness,
+            eq = keys.audio_preset,
+            sleep = keys.sleep_timer,
             f_add = keys.favourates_add,
             f_rm = keys.favourates_remove,
             srch = keys.start_search,
+            trend = keys.trending_category,
+            region = keys.trending_region,
+            comm = keys.community_add,
             view = keys.view,
             bkwd = keys.backward,
             frwd = keys.forward,
@@ -125,6 +141,9 @@ __   ___         _                           _
             quit = keys.quit,
             v_inc = keys.vol_increase,
             v_dec = keys.vol_decrease,
+            s_inc = keys.speed_up,
+            s_dec = keys.speed_down,
+            s_rst = keys.speed_reset,
         );
     }
 
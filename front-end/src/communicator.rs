@@ -2,264 +2,590 @@ use crate::ui::{
     self,
     event::{MIDDLE_ARTIST_INDEX, MIDDLE_MUSIC_INDEX, MIDDLE_PLAYLIST_INDEX},
 };
-use std::sync::{Arc, Condvar, Mutex};
+use config::initilize::CONFIG;
+use std::{
+    future::Future,
+    sync::{mpsc, Arc},
+    time::Duration,
+};
+use tokio::{
+    sync::mpsc::{UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+
+// Number of times to retry resolving the stream of next track. As fetcher rotates the server on
+// every request, each retry goes to different server
+const PREFETCH_RETRY_COUNT: u8 = 2;
+// Number of times to retry fetching a page of playlist being fed to the queue. Error is shown once
+// all of them fail. See `State::use_playlist_page`
+const PLAYLIST_PAGE_RETRY_COUNT: u8 = 2;
+
+// Work that communicator is asked to do. Event handler asks to fill the panes or to download
+// something and painter asks for things it finds missing while refreshing the player status.
+// See `request_pending`
+pub enum Command {
+    // Source, page and generation of the list to fill. See `State::fill_generation`
+    FillMusic(ui::MusicbarSource, usize, u64),
+    FillPlaylist(ui::PlaylistbarSource, usize, u64),
+    FillArtist(ui::ArtistbarSource, usize, u64),
+    // See ui::PrefetchState
+    Prefetch {
+        position: i64,
+        video_id: String,
+        attempt: u8,
+    },
+    // See ui::TrackGainState
+    TrackGain(String),
+    // See ui::AutoplayState
+    Autoplay(String),
+    // See ui::ChapterState
+    Chapters(String),
+    // See ui::SegmentSkipState
+    Segments(String),
+    // See ui::LyricsState
+    Lyrics(String),
+    // Id of channel to build the artist radio from
+    ArtistRadio(String),
+    // Text being typed in search box. See ui::SearchAssist
+    Suggestions(String),
+    // Id and page of playlist being fed to the queue. See ui::PlaylistFeed
+    PlaylistPage(String, usize),
+    // Url to download with youtube-dl
+    Download(String),
+}
+
+pub type CommandSender = UnboundedSender<Command>;
+
+// Outcome of a `Command` which is sent back to the painter. Painter is the only one who owns the
+// state so it is painter who fills these in state. See `Response::apply`
+pub enum Response {
+    // First member is the generation of list this was fetched for and second tells if it was
+    // served from local database instead of server. See fetcher::cache
+    Music(
+        u64,
+        bool,
+        Result<Vec<fetcher::MusicUnit>, fetcher::ReturnAction>,
+    ),
+    Playlist(
+        u64,
+        bool,
+        Result<Vec<fetcher::PlaylistUnit>, fetcher::ReturnAction>,
+    ),
+    Artist(
+        u64,
+        bool,
+        Result<Vec<fetcher::ArtistUnit>, fetcher::ReturnAction>,
+    ),
+    Prefetch {
+        position: i64,
+        video_id: String,
+        attempt: u8,
+        stream: Result<fetcher::AudioStream, fetcher::ReturnAction>,
+    },
+    TrackGain(String, Option<f64>),
+    Autoplay(Result<Vec<fetcher::MusicUnit>, fetcher::ReturnAction>),
+    Chapters(String, Vec<fetcher::Chapter>),
+    Segments(String, Vec<fetcher::SkipSegment>),
+    Lyrics(String, Vec<fetcher::lyrics::LyricLine>),
+    ArtistRadio(Result<Vec<fetcher::MusicUnit>, fetcher::ReturnAction>),
+    Suggestions(String, Vec<String>),
+    PlaylistPage(
+        String,
+        usize,
+        Result<Vec<fetcher::MusicUnit>, fetcher::ReturnAction>,
+    ),
+    DownloadFinished,
+}
 
+// Lists are filled at once so response may come in any order. When list is filled from new
+// source, musicbar always takes the focus but playlistbar and artistbar only take it if none of the
+// list is focused yet. So that focus ends up in musicbar after search as it used to when lists
+// were filled one after another. Pages loaded later while scrolling never move the focus
 macro_rules! handle_response {
-    ($response: expr, $state_original: expr, $win_index: expr, $target: ident) => {{
-        let mut state = $state_original.lock().unwrap();
-        // return the boolean which is only truw when response is RETRY
-        let mut need_retry = false;
+    ($generation: expr, $cached: expr, $response: expr, $state: expr, $win_index: expr, $target: ident, $window: expr) => {{
+        // List was asked to be filled again after this request was sent. eg: user searched again
+        // so this response is of no use anymore
+        if $generation != $state.fill_generation[$win_index] {
+            return;
+        }
         match $response {
             Ok(mut data) => {
-                state.status = "Success..";
+                $state.status = if $cached { "Cached.." } else { "Success.." };
                 data.shrink_to_fit();
-                state.$target.0 = data;
+                let is_new_source = $state.pages[$win_index].loaded.is_none();
+                $state.pages[$win_index].fill(
+                    &mut $state.$target,
+                    data,
+                    CONFIG.constants.item_per_list,
+                );
+                $state.pages[$win_index].follow_up(&$state.$target);
+                let list_focused = matches!(
+                    $state.active,
+                    ui::Window::Musicbar | ui::Window::Playlistbar | ui::Window::Artistbar
+                );
+                if is_new_source && ($window == ui::Window::Musicbar || !list_focused) {
+                    $state.active = $window;
+                }
             }
-            Err(e) => {
-                match e {
-                    fetcher::ReturnAction::Failed => {
-                        state.status = "Fetch error..";
-                    }
-                    fetcher::ReturnAction::EOR => {
-                        state.status = "Result end..";
-                        // TODO: Setting this to None means that the next page will always be 0.
-                        // That being said when user tries to navigate to previous page after seeing
-                        // EOR then still the fetched page will be 0. i.e again started from beginning.
-                        // This is desirable when user tries to navigate to next page but is
-                        // undesiriable when user tries to navigate to previous page. For now, I can't
-                        // think of any workaround except really messing around with fetched_page be
-                        // changing the data type (may be new struct storing maximum page before EOR)
-                        // and manipulating accordingly. But I have no intention to do so. So this todo
-                        // message will be left todo forever
-                        // -- END todo --
-                        // Setting this to None means that in next iteration condition
-                        // state.fetched_page[] != prev_<>_page wil be true but as for the whole if
-                        // statement to be true the page should be Some value. That means when EOR is
-                        // reched the list will be empty until next iteration. If the if condition
-                        // wouldn't have .is_some() check then in next iteration then zeroth page will
-                        // be fetched which is undesirable because it confuses weather it is reallt the
-                        // next page or zeroth page after EOR
-                        state.fetched_page[$win_index] = None;
-                    }
-                    fetcher::ReturnAction::Retry => {
-                        // the respective function from which the data is exptracted
-                        // specify the no of times to retry. Simple rerun the loop if retry is feasible
-                        state.status = "Retrying..";
-                        need_retry = true;
-                    }
+            Err(fetcher::ReturnAction::EOR) => {
+                $state.status = "Result end..";
+                // Page shown before is the last one and is kept in the list. Unless there was
+                // nothing at all
+                if $state.pages[$win_index].reached_end() {
+                    $state.$target.0.clear();
                 }
             }
+            // Retry is already handled by communicator. See `fill_with_retry!`
+            Err(_) => {
+                // Nothing is fetched from server while offline
+                $state.status = if fetcher::cache::is_offline() {
+                    "Not cached.."
+                } else {
+                    "Fetch error.."
+                };
+                // So that same page can be asked again
+                $state.pages[$win_index].requested = None;
+            }
         }
-        std::mem::drop(state);
-        need_retry
     }};
 }
 
-pub async fn communicator<'st, 'nt>(
-    state_original: &'st mut Arc<Mutex<ui::State<'_>>>,
-    notifier: &'nt mut Arc<Condvar>,
-) {
-    let mut fetcher = fetcher::Fetcher::default();
-
-    // variables with prev_ suffex are to be compared with respective current variables from state.
-    // This is to check weather anything have changed from previous data request from user so that
-    // further request are made or not
-    let (mut prev_musicbar_source, mut prev_playlistbar_source, mut prev_artistbar_source) = {
-        // Initilization is done inside seperate scope so that this state variable is not visible
-        // anywhere after that. It helps my autocomplete in editor
-        let state = state_original.lock().unwrap();
-        (
-            state.filled_source.0.clone(),
-            state.filled_source.1.clone(),
-            state.filled_source.2.clone(),
-        )
-    };
-    let mut prev_music_page: Option<usize> = None;
-    let mut prev_playlist_page: Option<usize> = None;
-    let mut prev_artist_page: Option<usize> = None;
-    // set these booleans to true when request handeling failed with RETREY response. if this is
-    // true then other condition should not have to be true
-    let mut need_retry = [false; 3];
-
-    'communicator_loop: loop {
-        let mut state = notifier.wait(state_original.lock().unwrap()).unwrap();
-        if state.active == ui::Window::None {
-            break 'communicator_loop;
+// Keep calling the fetcher as long as it asks to retry. Each try goes to different server as
+// fetcher rotates it on every request
+macro_rules! fill_with_retry {
+    ($fetch: expr) => {{
+        loop {
+            match $fetch {
+                Err(fetcher::ReturnAction::Retry) => continue,
+                response => break response,
+            }
         }
-
-        // This block is executed when the source of playlist has changed from previous iteration
-        // or new page is requested from the same source. Same pattern is repeated to fill musicbar
-        // amd artistbar too.
-        // As this statements are executed in loop in short time, creating the condition variable
-        // seperatly is worth sacrificing. Anyway It is true when one of below 3 condition are met:
-        // 1) the source of to fill playlist is different. i.e in previous loop data was shown from
-        //    search and now is needed to fetch the result of trending or seperate search query.
-        //    See PlaylistbarSource in ui/mod.rs
-        // 2) corresponsing retry is set to true
-        // 3) or the source is same but the different page is requested. An extra condition is
-        //    added to ensure that it is requesting at least Some page not nothing. eg: when EOR is
-        //    reached fetched_page is set to None and for None there is nothing to fetch. See EOR
-        //    condition in handle_response! macro
-        // UGH!! this if statement condition check is too ugly. I hate it
-        if state.filled_source.1 != prev_playlistbar_source
-            || need_retry[MIDDLE_PLAYLIST_INDEX]
-            || (state.fetched_page[MIDDLE_PLAYLIST_INDEX] != prev_playlist_page
-                && state.fetched_page[MIDDLE_PLAYLIST_INDEX].is_some())
-        {
-            // clear the target so that noone gets confused if it the response from previous or
-            // current request
-            state.playlistbar.0.clear();
-            state.status = "Fetch playlist..";
-
-            notifier.notify_one();
-
-            // condition of if made sure that fetched_page[MIDDLE_PLAYLIST_INDEX] is Some vlaue so
-            // unwrapping it is safe.
-            let page = state.fetched_page[MIDDLE_PLAYLIST_INDEX].unwrap();
-
-            // Save this source as previous source for next iteration
-            prev_playlistbar_source = state.filled_source.1.clone();
-            prev_playlist_page = Some(page);
-
-            // early drop the state so ui is not blocked while this thread send web req. See: else
-            // block documentation
-            std::mem::drop(state);
-
-            // This is the variable from which the response from matching source is set and later
-            // handled with handle_response! macro
-            let playlist_content;
-
-            // At this point state.filled.source.1 and prev_playlistbar_source is same. As state is
-            // already dropped we cant match state.filled.source.1 so match this
-            match prev_playlistbar_source {
-                ui::PlaylistbarSource::Search(ref term) => {
-                    playlist_content = fetcher.search_playlist(term, page).await;
-                }
-                ui::PlaylistbarSource::Artist(ref artist_id) => {
-                    playlist_content = fetcher.get_playlist_of_channel(artist_id, page).await;
-                }
-                ui::PlaylistbarSource::Favourates => {
-                    playlist_content = fetcher.get_favourates_playlist(page).await;
-                }
-                ui::PlaylistbarSource::RecentlyPlayed => {
-                    // TODO
-                    playlist_content = Ok(Vec::new());
-                }
+    }};
+    // Give up after retrying these many times
+    ($fetch: expr, $retry_count: expr) => {{
+        let mut retried = 0;
+        loop {
+            match $fetch {
+                Err(fetcher::ReturnAction::Retry) if retried < $retry_count => retried += 1,
+                response => break response,
             }
+        }
+    }};
+}
 
-            // if return action is RETRY set so in need_retry so that nex interation will try again
-            let retry = handle_response!(
-                playlist_content,
-                state_original,
+impl Response {
+    // Fill the response in state. This is called by painter as it owns the state
+    pub fn apply(self, state: &mut ui::State) {
+        match self {
+            Response::Music(generation, cached, music) => handle_response!(
+                generation,
+                cached,
+                music,
+                state,
+                MIDDLE_MUSIC_INDEX,
+                musicbar,
+                ui::Window::Musicbar
+            ),
+            Response::Playlist(generation, cached, playlist) => handle_response!(
+                generation,
+                cached,
+                playlist,
+                state,
                 MIDDLE_PLAYLIST_INDEX,
-                playlistbar
-            );
-            need_retry[MIDDLE_PLAYLIST_INDEX] = retry;
-            state_original.lock().unwrap().active = ui::Window::Playlistbar;
-            notifier.notify_one();
-        } else {
-            // State is always unlocked in above block and dropped in if block. But when if block
-            // condition is not met then the state will never be unlocked so always drop the state.
-            // Note, insetad of dropping state inside and outside if statement one could think to
-            // drop it outside the if/else block so single drop statement will work. But when if
-            // block is executed web request is made and when state is dropped outside if/else
-            // block then the state will remain locked until web request is made which may even
-            // take indefinite time so which in turn keeps the ui blocked state is dropped early
-            // in if block
-            std::mem::drop(state);
+                playlistbar,
+                ui::Window::Playlistbar
+            ),
+            Response::Artist(generation, cached, artist) => handle_response!(
+                generation,
+                cached,
+                artist,
+                state,
+                MIDDLE_ARTIST_INDEX,
+                artistbar,
+                ui::Window::Artistbar
+            ),
+            Response::Prefetch {
+                position,
+                video_id,
+                attempt,
+                stream,
+            } => match stream {
+                Ok(stream) => state.use_prefetched(position, stream),
+                Err(_) => {
+                    state.status = "Prefetch failed..";
+                    // Track may have changed meanwhile and new request is already placed
+                    if state.prefetch.request == ui::Prefetch::Pending {
+                        state.prefetch.request = if attempt < PREFETCH_RETRY_COUNT {
+                            ui::Prefetch::Request {
+                                position,
+                                video_id,
+                                attempt: attempt + 1,
+                            }
+                        } else {
+                            // Give up. Player will resolve it by itself as usual
+                            ui::Prefetch::Idle
+                        };
+                    }
+                }
+            },
+            Response::TrackGain(video_id, loudness) => state.use_track_gain(video_id, loudness),
+            Response::Autoplay(recommended) => match recommended {
+                Ok(recommended) => state.use_recommended(recommended),
+                Err(_) => state.status = "Autoplay failed..",
+            },
+            Response::Chapters(video_id, chapters) => state.use_chapters(&video_id, chapters),
+            Response::Segments(video_id, segments) => state.use_segments(&video_id, segments),
+            Response::Lyrics(video_id, lines) => state.use_lyrics(&video_id, lines),
+            Response::ArtistRadio(mix) => match mix {
+                Ok(mix) => state.start_artist_radio(mix),
+                Err(_) => state.status = "Radio failed..",
+            },
+            Response::Suggestions(query, suggestions) => state.use_suggestions(&query, suggestions),
+            Response::PlaylistPage(playlist_id, page, music) => {
+                state.use_playlist_page(&playlist_id, page, music)
+            }
+            Response::DownloadFinished => {
+                state.downloads = state.downloads.saturating_sub(1);
+            }
         }
+    }
+}
+
+// Painter places the request in state while refreshing the player status. Take those requests and
+// send them to communicator. This is called by painter after every refresh
+pub fn request_pending(state: &mut ui::State, commands: &CommandSender) {
+    let send = |command| {
+        // Communicator only stops after painter so this should not fail. Anyway if it does there
+        // is no one to fill the response so just ignore
+        commands.send(command).ok();
+    };
 
-        // Checks and fills the artistbar.
-        let mut state = state_original.lock().unwrap();
-        if state.filled_source.2 != prev_artistbar_source
-            || need_retry[MIDDLE_ARTIST_INDEX]
-            || (state.fetched_page[MIDDLE_ARTIST_INDEX] != prev_artist_page
-                && state.fetched_page[MIDDLE_ARTIST_INDEX].is_some())
+    if let ui::Prefetch::Request { .. } = state.prefetch.request {
+        if let ui::Prefetch::Request {
+            position,
+            video_id,
+            attempt,
+        } = std::mem::replace(&mut state.prefetch.request, ui::Prefetch::Pending)
         {
-            state.artistbar.0.clear();
-            state.status = "Fetch artists..";
-            notifier.notify_one();
-
-            let page = state.fetched_page[MIDDLE_ARTIST_INDEX].unwrap();
-            prev_artistbar_source = state.filled_source.2.clone();
-            prev_artist_page = Some(page);
-            std::mem::drop(state);
-
-            let artist_content;
-            match prev_artistbar_source {
-                ui::ArtistbarSource::Search(ref term) => {
-                    artist_content = fetcher.search_artist(term, page).await;
-                }
-                ui::ArtistbarSource::Favourates => {
-                    artist_content = fetcher.get_favourates_artist(page).await;
-                }
-                ui::ArtistbarSource::RecentlyPlayed => {
-                    // TODO:
-                    artist_content = Ok(Vec::new());
-                }
-            }
+            send(Command::Prefetch {
+                position,
+                video_id,
+                attempt,
+            });
+        }
+    }
+    for index in [
+        MIDDLE_MUSIC_INDEX,
+        MIDDLE_PLAYLIST_INDEX,
+        MIDDLE_ARTIST_INDEX,
+    ] {
+        if let Some(page) = state.pages[index].follow_up.take() {
+            ui::event::fill_list(state, commands, index, page);
+        }
+    }
+    if let Some(video_id) = state.track_gain.request.take() {
+        send(Command::TrackGain(video_id));
+    }
+    if let Some(video_id) = state.autoplay.request.take() {
+        send(Command::Autoplay(video_id));
+    }
+    if let Some(video_id) = state.chapters.request.take() {
+        send(Command::Chapters(video_id));
+    }
+    if let Some(video_id) = state.segments.request.take() {
+        send(Command::Segments(video_id));
+    }
+    if let Some(video_id) = state.lyrics.request.take() {
+        send(Command::Lyrics(video_id));
+    }
+    if let Some(channel_id) = state.artist_radio.take() {
+        state.status = "Building radio..";
+        send(Command::ArtistRadio(channel_id));
+    }
+    if let Some(query) = state.take_suggestions_request() {
+        send(Command::Suggestions(query));
+    }
+    if let Some(feed) = state.playlist_feed.as_mut() {
+        if let Some(page) = feed.request.take() {
+            send(Command::PlaylistPage(feed.id.clone(), page));
+        }
+    }
+}
 
-            let retry = handle_response!(
-                artist_content,
-                state_original,
-                MIDDLE_ARTIST_INDEX,
-                artistbar
-            );
-            need_retry[MIDDLE_ARTIST_INDEX] = retry;
-            state_original.lock().unwrap().active = ui::Window::Artistbar;
-            notifier.notify_one();
-        } else {
-            std::mem::drop(state);
+type OwnedFetcher = Arc<tokio::sync::Mutex<fetcher::Fetcher>>;
+
+// Music, playlist and artist list are filled at once each with it's own fetcher so that one list
+// do not wait for another. Only one request is in flight for a list. When asked to fill the list
+// again (eg: new search query) the previous request is cancelled so that it can never overwrite
+// the list with result of old query
+struct ListFiller {
+    // Fetcher is also moved to the spawned task so it is locked there. Cancelled task releases the
+    // lock as it is dropped
+    fetcher: OwnedFetcher,
+    in_flight: Option<JoinHandle<()>>,
+}
+
+impl ListFiller {
+    fn new() -> Self {
+        ListFiller {
+            fetcher: Arc::new(tokio::sync::Mutex::new(fetcher::Fetcher::default())),
+            in_flight: None,
         }
+    }
 
-        // Checks and fills the musicbar
-        let mut state = state_original.lock().unwrap();
-        if state.filled_source.0 != prev_musicbar_source
-            || need_retry[MIDDLE_MUSIC_INDEX]
-            || (state.fetched_page[MIDDLE_MUSIC_INDEX] != prev_music_page
-                && state.fetched_page[MIDDLE_MUSIC_INDEX].is_some())
-        {
-            state.musicbar.0.clear();
-            state.status = "Fetch music..";
-            notifier.notify_one();
-
-            let page = state.fetched_page[MIDDLE_MUSIC_INDEX].unwrap();
-            prev_musicbar_source = state.filled_source.0.clone();
-            prev_music_page = Some(page);
-            std::mem::drop(state);
-            // prev_musicbar_source and current musicbar_source are equal at this point
-            let music_content;
-            match prev_musicbar_source {
-                ui::MusicbarSource::Trending => {
-                    music_content = fetcher.get_trending_music(page).await;
-                }
-                ui::MusicbarSource::Search(ref term) => {
-                    music_content = fetcher.search_music(term, page).await;
-                }
-                ui::MusicbarSource::Playlist(ref playlist_id) => {
-                    music_content = fetcher.get_playlist_content(playlist_id, page).await;
-                }
-                ui::MusicbarSource::Artist(ref artist_id) => {
-                    music_content = fetcher.get_videos_of_channel(artist_id, page).await;
-                }
-                ui::MusicbarSource::Favourates => {
-                    music_content = fetcher.get_favourates_music(page).await;
-                }
-                ui::MusicbarSource::RecentlyPlayed => {
-                    // TODO: handle each variant with accurate function
-                    music_content = Ok(Vec::new());
-                }
+    // Cancel whatever was being fetched for this list and start fetching the new one
+    fn fill<F>(&mut self, painter: &mpsc::Sender<ui::Message>, fill: impl FnOnce(OwnedFetcher) -> F)
+    where
+        F: Future<Output = Response> + Send + 'static,
+    {
+        if let Some(previous) = self.in_flight.take() {
+            previous.abort();
+        }
+
+        let response = fill(Arc::clone(&self.fetcher));
+        let painter = painter.clone();
+        self.in_flight = Some(tokio::task::spawn(async move {
+            painter.send(ui::Message::Fetched(response.await)).ok();
+        }));
+    }
+}
+
+async fn fill_music(
+    fetcher: OwnedFetcher,
+    source: ui::MusicbarSource,
+    page: usize,
+    generation: u64,
+) -> Response {
+    let mut fetcher = fetcher.lock().await;
+    let result = fill_with_retry!(match source {
+        ui::MusicbarSource::Trending(ref region, category) => {
+            fetcher.get_trending(region, category, page).await
+        }
+        ui::MusicbarSource::Search(ref term) => fetcher.search_music(term, page).await,
+        ui::MusicbarSource::Playlist(ref playlist_id) => {
+            fetcher.get_playlist_content(playlist_id, page).await
+        }
+        ui::MusicbarSource::Artist(ref artist_id) => {
+            fetcher.get_videos_of_channel(artist_id, page).await
+        }
+        ui::MusicbarSource::Favourates => fetcher.get_favourates_music(page).await,
+        // TODO: handle each variant with accurate function
+        ui::MusicbarSource::RecentlyPlayed => Ok(Vec::new()),
+    });
+    Response::Music(generation, fetcher.take_cache_hit(), result)
+}
+
+async fn fill_playlist(
+    fetcher: OwnedFetcher,
+    source: ui::PlaylistbarSource,
+    page: usize,
+    generation: u64,
+) -> Response {
+    let mut fetcher = fetcher.lock().await;
+    let result = fill_with_retry!(match source {
+        ui::PlaylistbarSource::Search(ref term) => fetcher.search_playlist(term, page).await,
+        ui::PlaylistbarSource::Artist(ref artist_id) => {
+            fetcher.get_playlist_of_channel(artist_id, page).await
+        }
+        ui::PlaylistbarSource::Favourates => fetcher.get_favourates_playlist(page).await,
+        // TODO
+        ui::PlaylistbarSource::RecentlyPlayed => Ok(Vec::new()),
+    });
+    Response::Playlist(generation, fetcher.take_cache_hit(), result)
+}
+
+async fn fill_artist(
+    fetcher: OwnedFetcher,
+    source: ui::ArtistbarSource,
+    page: usize,
+    generation: u64,
+) -> Response {
+    let mut fetcher = fetcher.lock().await;
+    let result = fill_with_retry!(match source {
+        ui::ArtistbarSource::Search(ref term) => fetcher.search_artist(term, page).await,
+        ui::ArtistbarSource::Favourates => fetcher.get_favourates_artist(page).await,
+        // TODO:
+        ui::ArtistbarSource::RecentlyPlayed => Ok(Vec::new()),
+    });
+    Response::Artist(generation, fetcher.take_cache_hit(), result)
+}
+
+// Fetcher for each kind of command other than filling the lists. Those commands are run in their
+// own task with the fetcher of their kind so that eg: slow lyrics do not hold back the prefetch of
+// next track. Commands of same kind still wait for one another as they share the fetcher
+#[derive(Default)]
+struct Workers {
+    prefetch: OwnedFetcher,
+    track_gain: OwnedFetcher,
+    autoplay: OwnedFetcher,
+    chapters: OwnedFetcher,
+    segments: OwnedFetcher,
+    lyrics: OwnedFetcher,
+    artist_radio: OwnedFetcher,
+    suggestions: OwnedFetcher,
+    playlist_page: OwnedFetcher,
+}
+
+// Run the work in it's own task with given fetcher and send the response to painter once it is
+// done. Nothing is sent if work gives None
+fn spawn_work<F>(
+    fetcher: &OwnedFetcher,
+    painter: &mpsc::Sender<ui::Message>,
+    work: impl FnOnce(OwnedFetcher) -> F,
+) where
+    F: Future<Output = Option<Response>> + Send + 'static,
+{
+    let response = work(Arc::clone(fetcher));
+    let painter = painter.clone();
+    tokio::task::spawn(async move {
+        if let Some(response) = response.await {
+            painter.send(ui::Message::Fetched(response)).ok();
+        }
+    });
+}
+
+// Communicator is the only one talking to fetcher. It waits for the command and starts the work in
+// it's own task which sends back the response to painter. Loop is finished once painter quits and
+// drops the sender of commands. See `ListFiller` and `Workers`
+pub async fn communicator(
+    mut commands: UnboundedReceiver<Command>,
+    painter: mpsc::Sender<ui::Message>,
+) {
+    let mut fillers = [ListFiller::new(), ListFiller::new(), ListFiller::new()];
+    let workers = Workers::default();
+
+    while let Some(command) = commands.recv().await {
+        match command {
+            Command::FillMusic(source, page, generation) => {
+                fillers[MIDDLE_MUSIC_INDEX].fill(&painter, |fetcher| {
+                    fill_music(fetcher, source, page, generation)
+                });
+            }
+            Command::FillPlaylist(source, page, generation) => {
+                fillers[MIDDLE_PLAYLIST_INDEX].fill(&painter, |fetcher| {
+                    fill_playlist(fetcher, source, page, generation)
+                });
+            }
+            Command::FillArtist(source, page, generation) => {
+                fillers[MIDDLE_ARTIST_INDEX].fill(&painter, |fetcher| {
+                    fill_artist(fetcher, source, page, generation)
+                });
+            }
+
+            Command::Prefetch {
+                position,
+                video_id,
+                attempt,
+            } => spawn_work(&workers.prefetch, &painter, |fetcher| async move {
+                let stream = fetcher.lock().await.get_audio_stream(&video_id).await;
+                Some(Response::Prefetch {
+                    position,
+                    video_id,
+                    attempt,
+                    stream,
+                })
+            }),
+
+            Command::TrackGain(video_id) => {
+                spawn_work(&workers.track_gain, &painter, |fetcher| async move {
+                    let loudness = fetcher.lock().await.get_loudness(&video_id).await;
+                    Some(Response::TrackGain(video_id, loudness.ok().flatten()))
+                })
+            }
+
+            Command::Autoplay(video_id) => {
+                spawn_work(&workers.autoplay, &painter, |fetcher| async move {
+                    let recommended = fetcher.lock().await.get_recommended(&video_id).await;
+                    Some(Response::Autoplay(recommended))
+                })
+            }
+
+            Command::Chapters(video_id) => {
+                spawn_work(&workers.chapters, &painter, |fetcher| async move {
+                    let chapters = fetcher.lock().await.get_chapters(&video_id).await;
+                    chapters
+                        .ok()
+                        .map(|chapters| Response::Chapters(video_id, chapters))
+                })
             }
 
-            let retry =
-                handle_response!(music_content, state_original, MIDDLE_MUSIC_INDEX, musicbar);
-            need_retry[MIDDLE_MUSIC_INDEX] = retry;
-            state_original.lock().unwrap().active = ui::Window::Musicbar;
-            notifier.notify_one();
-        } else {
-            // If above if block is not executed state lock should however be released
-            // so that state can be lock again for following if block
-            std::mem::drop(state);
+            Command::Segments(video_id) => {
+                spawn_work(&workers.segments, &painter, |fetcher| async move {
+                    let segments = fetcher.lock().await.get_skip_segments(&video_id).await;
+                    segments
+                        .ok()
+                        .map(|segments| Response::Segments(video_id, segments))
+                })
+            }
+
+            Command::Lyrics(video_id) => {
+                spawn_work(&workers.lyrics, &painter, |fetcher| async move {
+                    let lines = fetcher
+                        .lock()
+                        .await
+                        .get_captions(&video_id, &CONFIG.playback.lyrics_languages)
+                        .await;
+                    Some(Response::Lyrics(video_id, lines.unwrap_or_default()))
+                })
+            }
+
+            Command::ArtistRadio(channel_id) => {
+                spawn_work(&workers.artist_radio, &painter, |fetcher| async move {
+                    let mix = fetcher.lock().await.get_artist_mix(&channel_id).await;
+                    Some(Response::ArtistRadio(mix))
+                })
+            }
+
+            // Suggestions are not that important to retry or report
+            Command::Suggestions(query) => {
+                spawn_work(&workers.suggestions, &painter, |fetcher| async move {
+                    let suggestions = fetcher.lock().await.get_search_suggestions(&query).await;
+                    Some(Response::Suggestions(
+                        query,
+                        suggestions.unwrap_or_default(),
+                    ))
+                })
+            }
+
+            Command::PlaylistPage(playlist_id, page) => {
+                spawn_work(&workers.playlist_page, &painter, |fetcher| async move {
+                    let mut fetcher = fetcher.lock().await;
+                    let music = fill_with_retry!(
+                        fetcher.get_playlist_page(&playlist_id, page).await,
+                        PLAYLIST_PAGE_RETRY_COUNT
+                    );
+                    Some(Response::PlaylistPage(playlist_id, page, music))
+                })
+            }
+
+            Command::Download(url) => start_download(url, painter.clone()),
         }
     }
 }
+
+// Spawn youtube-dl in background and let painter know once it exits so that painter can tell if
+// any download is in progress
+fn start_download(url: String, painter: mpsc::Sender<ui::Message>) {
+    let mut command = tokio::process::Command::new("youtube-dl");
+    command
+        .arg(url)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .args(["--extract-audio", "--audio-format", &CONFIG.download.format])
+        .current_dir(&CONFIG.download.path)
+        .kill_on_drop(false);
+
+    // Wait for 5 second just to make sure that command has finished executing.
+    // It usually donot take all those 5 seconds
+    // Anyway, download won't finish before 5 seconds
+    // Then just wait for command to finish by waiting for exit status
+    // and let painter decrease the download queue count
+    tokio::task::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        command.status().await.ok();
+        painter
+            .send(ui::Message::Fetched(Response::DownloadFinished))
+            .ok();
+    });
+}
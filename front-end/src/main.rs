@@ -1,6 +1,4 @@
-use std::sync::Arc;
-use std::sync::Condvar;
-use std::sync::Mutex;
+use std::sync::mpsc;
 use std::thread;
 mod cli;
 mod communicator;
@@ -9,14 +7,15 @@ mod ui;
 /*
 * The role of main function is just to spwan two different loop in each thread and again pass
 * control to another loop
-* 1) handler is a sync thread for event_listener: This thread will wait for user input and respond
-*    See ui/event.rs for implementation
-* 2) comminucate is the sync thread for the comminucator which act as the bridge bwteen backend and
-*    front-end. It checks for data required, get data from fetcher and also handles the filling of
-*    data in respective place
-* And the main thread itself will pass the control to `draw_ui` which handles rendering or painting
-* to the terminal. This painter function as well as other 2 spawned thread above runs in a loop and
-* all those loop and terminated when user hits key to quit the application.
+* 1) listener is a sync thread for event_listener: This thread will wait for user input and send
+*    it to the painter. See ui/event.rs for implementation
+* 2) comminucate is the thread for the comminucator which act as the bridge bwteen backend and
+*    front-end. It waits for the command, get data from fetcher and sends the response back to the
+*    painter
+* And the main thread itself will pass the control to `draw_ui` which owns the state, handles the
+* input and response and does rendering or painting to the terminal. This painter function as well
+* as other 2 spawned thread above runs in a loop and all those loop and terminated when user hits
+* key to quit the application.
 *
 * See below files for respective function
 * __ui/mod.rs__: Defines structures as well as draw_ui function which render the content. This files
@@ -26,15 +25,18 @@ mod ui;
 * includes building the individual components, defining styles and layout, Initilizing the state
 * and other structs.
 *
-* __ui/event.rs__: The sole purpose of this file is to wait for user event and responds by either
+* __ui/event.rs__: The sole purpose of this file is to wait for user event and respond by either
 * updating the ui or by asking the comminucator to fill the required data
 *
-* __communicator.rs__: This file reads the state variable, compares it to previous state and change
-* the data to be rendered. This includes calling the fetcher backed, navigating pages and so on.
+* __communicator.rs__: This file receives the command, calls the fetcher backend and sends the
+* response which painter then fills in the state.
 *
-* All the comminucation required are done via a single state variable which stores the state as
-* well as presented data. Given state variable is shared via wrapping in condavr so that one thread
-* can notify other thread when it bring some change in state
+* Nothing is shared between the threads. State is owned by the painter and all the comminucation
+* is done via channels:
+* - event listener and communicator sends `ui::Message` to painter. Painter repaints the ui after
+*   handling them
+* - painter (while handling the input or refreshing the player status) sends
+*   `communicator::Command` to communicator
 */
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -57,47 +59,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    let state = Arc::new(Mutex::new(ui::State::default()));
-    let cvar = Arc::new(Condvar::new());
+    let (painter_sender, painter_inbox) = mpsc::channel();
+    let (command_sender, command_receiver) = tokio::sync::mpsc::unbounded_channel();
 
-    let (handler, communicate);
-    {
-        // same state is shared among all thread
-        let mut state_for_painter = Arc::clone(&state);
-        let mut state_for_handler = Arc::clone(&state);
-        let mut state_for_communicator = Arc::clone(&state);
-        let mut cvar_for_painter = Arc::clone(&cvar);
-        let mut cvar_for_handler = Arc::clone(&cvar);
-        let mut cvar_for_communicator = Arc::clone(&cvar);
-
-        handler = thread::spawn(move || {
-            tokio::runtime::Builder::new_multi_thread()
-                .enable_all()
-                .build()
-                .unwrap()
-                .block_on(async move {
-                    ui::event::event_sender(&mut state_for_handler, &mut cvar_for_handler).await;
-                });
-        });
+    let listener = {
+        let painter_sender = painter_sender.clone();
+        thread::spawn(move || ui::event::event_listener(painter_sender))
+    };
+    let communicate = thread::spawn(move || {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(communicator::communicator(command_receiver, painter_sender));
+    });
 
-        communicate = thread::spawn(move || {
-            tokio::runtime::Builder::new_multi_thread()
-                .enable_all()
-                .build()
-                .unwrap()
-                .block_on(async move {
-                    communicator::communicator(
-                        &mut state_for_communicator,
-                        &mut cvar_for_communicator,
-                    )
-                    .await;
-                });
-        });
-
-        ui::draw_ui(&mut state_for_painter, &mut cvar_for_painter);
-    }
+    // Once painter returns, command sender is dropped which stops the communicator and event
+    // listener stops as soon as it fails to send the next message
+    ui::draw_ui(ui::State::default(), painter_inbox, command_sender);
 
-    handler.join().unwrap();
+    listener.join().unwrap();
     communicate.join().unwrap();
 
     println!();
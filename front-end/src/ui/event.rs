@@ -1,11 +1,8 @@
-use crate::ui::{self, utils::ExtendMpv};
+use crate::communicator::{Command, CommandSender};
+use crate::ui;
 use config::initilize::{CONFIG, STORAGE};
 use crossterm::event::{self, Event, KeyCode, KeyModifiers};
-use std::{
-    convert::TryFrom,
-    sync::{Arc, Condvar, Mutex},
-    time::Duration,
-};
+use std::{sync::mpsc, time::Duration};
 
 pub const MIDDLE_MUSIC_INDEX: usize = 0;
 pub const MIDDLE_PLAYLIST_INDEX: usize = 1;
@@ -32,755 +29,750 @@ fn advance_index(current: usize, limit: usize, direction: HeadTo) -> usize {
     }
 }
 
-// Helper function to drop the first paramater and call the function in second paramater and
-// optional arguments provided in later arguments
-// This is used to drop the state and call the function as such pattern is found redundant while
-// calling event handeling closure where unlocked state needs to be droppped before calling the
-// corresponding handler
-macro_rules! drop_and_call {
-    // This will call the function in passe in second argument
-    // passed function will not accept any argument
-    ($state: expr, $callback: expr) => {{
-        std::mem::drop($state);
-        $callback()
-    }};
-    // This will call the function recived in second argument and pass the later arguments as that
-    // function paramater
-    ($state: expr, $callback: expr, $($args: expr)*) => {{
-        std::mem::drop($state);
-        $callback( $($args)* )
-    }};
-}
-
-// Heklper function to get the next page depending on the current page and direction to move
-// This was mainly created to fetch the next page of the musicbar/playlist bar when user
-// hits NEXT_SH_KEY or PREV_SH_KEY
-#[inline]
-fn get_page(current: &Option<usize>, direction: HeadTo) -> usize {
-    let page = match current {
-        None => 0,
-        Some(prev) => match direction {
-            HeadTo::Initial => 0,
-            HeadTo::Next => prev + 1,
-            HeadTo::Prev => prev.checked_sub(1).unwrap_or_default(),
-        },
-    };
-    page as usize
-}
-
 /*
-* The event_sender function is running in it's own seperate thread.
+* The event_listener function is running in it's own seperate thread.
 * -> A loop is initilized where it waits for any event to happen (keypress and resize for now)
-* and call the corresponding closure to handle event.
-* -> Inside every closure state that are dependent to this event is checked. eg: checks active
+* and send it to painter which owns the state. Painter then calls `handle_event` which calls the
+* corresponding function to handle the event.
+* -> Inside every function state that are dependent to this event is checked. eg: checks active
 * window shile handleing left/right direction key
-* -> To fetch data, required data paramater is set in a state variable which is shared across all
-* the threads. And another loop is ran in communicator.rs where it wait checks weather anything
-* should be filled from diffrenet source.
+* -> To fetch data, required data paramater is set in state and the command is sent to communicator
+* over the channel. Communicator sends back the response to painter which fills it. See
+* communicator.rs
 */
-pub async fn event_sender(
-    state_original: &mut Arc<Mutex<ui::State<'_>>>,
-    notifier: &mut Arc<Condvar>,
-) {
-    // Some predefined source
-    let youtube_community_channels = vec![fetcher::ArtistUnit {
-        name: "Youtube Music Global Charts".to_string(),
-        id: "UCrKZcyOJVWnJ60zM1XWllNw".to_string(),
-        video_count: "NaN".to_string(),
-    }];
-
-    let download_counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
-
-    // There is several option in sidebar like trending/ favourates,
-    // this handler will change the selected option from sidebar depending on the direction user
-    // move (Up or DOwn).
-    let advance_sidebar = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        let current = state.sidebar.selected().unwrap_or_default();
-        state.sidebar.select(Some(advance_index(
-            current,
-            ui::utils::SIDEBAR_LIST_COUNT,
-            direction,
-        )));
-        notifier.notify_all();
-    };
+pub fn event_listener(painter: mpsc::Sender<ui::Message>) {
+    loop {
+        let message = if event::poll(Duration::from_millis(CONFIG.constants.refresh_rate)).unwrap()
+        {
+            ui::Message::Input(event::read().unwrap())
+        } else {
+            ui::Message::Tick
+        };
 
-    // select the next or previous element in musicbar list. This is done simply by setting the
-    // correct index in corresponding TableState
-    let advance_music_list = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        let next_index;
-        match state.musicbar.1.selected() {
-            None => next_index = 0,
-            Some(current) => {
-                next_index = advance_index(current, state.musicbar.0.len(), direction);
-            }
+        // Painter is gone. i.e user asked to quit
+        if painter.send(message).is_err() {
+            break;
         }
-        state.musicbar.1.select(Some(next_index));
-        notifier.notify_all();
-    };
+    }
+}
 
-    // simialr to advance_music_list but instead rotate data in `playlistbar` variable of state
-    let advance_playlist_list = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        let next_index;
-        match state.playlistbar.1.selected() {
-            None => next_index = 0,
-            Some(current) => {
-                next_index = advance_index(current, state.playlistbar.0.len(), direction);
-            }
+// Ask communicator to fill the list at `index` with given page from the source set in state.
+// List is kept as it is until the page is fetched. See `communicator::Response::apply`
+pub fn fill_list(state: &mut ui::State, commands: &CommandSender, index: usize, page: usize) {
+    state.pages[index].requested = Some(page);
+    state.fill_generation[index] += 1;
+    let generation = state.fill_generation[index];
+    let command = match index {
+        MIDDLE_MUSIC_INDEX => {
+            state.status = "Fetch music..";
+            Command::FillMusic(state.filled_source.0.clone(), page, generation)
         }
-        state.playlistbar.1.select(Some(next_index));
-        notifier.notify_all();
-    };
-
-    // simialr to advance_playlist_list but instead rotate data in `artistbar` variable of state
-    let advance_artist_list = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        // if the list is empty then do nothing else.
-        // It is necessary to return instantly otherwise the next_index will get value 0 and this
-        // closure will endup doing select(Some(0)) to the empty list
-        let next_index;
-        match state.artistbar.1.selected() {
-            None => next_index = 0,
-            Some(current) => {
-                next_index = advance_index(current, state.artistbar.0.len(), direction);
-            }
+        MIDDLE_PLAYLIST_INDEX => {
+            state.status = "Fetch playlist..";
+            Command::FillPlaylist(state.filled_source.1.clone(), page, generation)
         }
-        state.artistbar.1.select(Some(next_index));
-        notifier.notify_all();
+        MIDDLE_ARTIST_INDEX => {
+            state.status = "Fetch artists..";
+            Command::FillArtist(state.filled_source.2.clone(), page, generation)
+        }
+        _ => unreachable!(),
     };
+    // Communicator only stops after painter so this should not fail
+    commands.send(command).ok();
+}
 
-    // When active window is set to NONE, it means user had requested to quit the application,
-    // This handle will fire when user hits QUIT_SH_KEY
-    // Before breaking the loop which this function is running on
-    // this closure will simply set the active_window (`active`) to None so that functions in other
-    // thread can also respond to the event (which is usally again breking the running loop in
-    // thread)
-    let quit = |force_quit: bool| -> bool {
-        let mut state = state_original.lock().unwrap();
-        // Do not quit when some download is in progress as it may leave partial file on the disk.
-        // If it is urgent required to quit the application user should also press ALT key along
-        // with CTRL and QUIT key
-        if !force_quit && *download_counter.lock().unwrap() > 0 {
-            state.active = ui::Window::Popup(
-                "Error",
-                "Some download are in progress. Press this shortcut with ALT key to force quit"
-                    .to_string(),
-            );
-            return false;
+// Fill the list at `index` from the first page. Called when source of the list is changed so
+// clear the target so that noone gets confused if it the result from previous or current source
+fn refill_list(state: &mut ui::State, commands: &CommandSender, index: usize) {
+    state.pages[index] = ui::Pagination::default();
+    match index {
+        MIDDLE_MUSIC_INDEX => state.musicbar.0.clear(),
+        MIDDLE_PLAYLIST_INDEX => state.playlistbar.0.clear(),
+        MIDDLE_ARTIST_INDEX => state.artistbar.0.clear(),
+        _ => unreachable!(),
+    }
+    fill_list(state, commands, index, 0);
+}
+
+// There is several option in sidebar like trending/ favourates,
+// this handler will change the selected option from sidebar depending on the direction user
+// move (Up or DOwn).
+fn advance_sidebar(state: &mut ui::State, direction: HeadTo) {
+    let current = state.sidebar.selected().unwrap_or_default();
+    let count = ui::utils::SIDEBAR_LIST_COUNT + state.community.groups().len();
+    state
+        .sidebar
+        .select(Some(advance_index(current, count, direction)));
+}
+
+// Move the cursor of list at `index` by `rows` (negative to move up). This is done simply by
+// setting the correct index in corresponding TableState. Next or previous page is fetched in
+// background once cursor gets near the end of list. See `ui::Pagination::scroll`
+fn scroll_list(state: &mut ui::State, commands: &CommandSender, index: usize, rows: isize) {
+    let pages = &state.pages[index];
+    let to_load = match index {
+        MIDDLE_MUSIC_INDEX => pages.scroll(&mut state.musicbar, rows),
+        MIDDLE_PLAYLIST_INDEX => pages.scroll(&mut state.playlistbar, rows),
+        MIDDLE_ARTIST_INDEX => pages.scroll(&mut state.artistbar, rows),
+        _ => unreachable!(),
+    };
+    if let Some(page) = to_load {
+        fill_list(state, commands, index, page);
+    }
+}
+
+// This handle will fire when user hits QUIT_SH_KEY
+// Return true if application should quit. Painter then breaks it's loop which in turn stops
+// the event listener and communicator as channel to painter is closed
+fn quit(state: &mut ui::State, force_quit: bool) -> bool {
+    // Do not quit when some download is in progress as it may leave partial file on the disk.
+    // If it is urgent required to quit the application user should also press ALT key along
+    // with CTRL and QUIT key
+    if !force_quit && state.downloads > 0 {
+        state.active = ui::Window::Popup(
+            "Error",
+            "Some download are in progress. Press this shortcut with ALT key to force quit"
+                .to_string(),
+        );
+        return false;
+    }
+
+    state.active = ui::Window::None;
+    // Also make sure databse is flushed.
+    if let Err(err) = STORAGE.lock().unwrap().cache_flush() {
+        eprintln!("Cannot flush the storage db. Error: {err}", err = err);
+    }
+
+    true
+}
+
+// This handler will fire up when user request to move between sections like musicbar, sidebar
+// etc. Similar handler moveto_next_window / moveto_prev_window are not merged as these
+// handlers are frequently called so avoid more branching
+fn moveto_next_window(state: &mut ui::State) {
+    state.active = state.active.next();
+}
+
+fn moveto_prev_window(state: &mut ui::State) {
+    state.active = state.active.prev();
+}
+
+// This handler is fired when user press ESC key,
+// if searchbar is active clear the content in search bar and move to next window
+// if helpbar is active anway move to sidebar just to hide the help window
+fn handle_esc(state: &mut ui::State) {
+    match state.active {
+        ui::Window::Searchbar | ui::Window::Popup(..) => {
+            state.search.0.clear();
+            state.search_edited();
+            moveto_next_window(state);
+        }
+        ui::Window::BottomControl => {
+            moveto_next_window(state);
         }
+        ui::Window::Sidebar
+        | ui::Window::Musicbar
+        | ui::Window::Playlistbar
+        | ui::Window::Artistbar => {
+            state.active = ui::Window::BottomControl;
+        }
+        ui::Window::None => {
+            unreachable!();
+        }
+    }
+}
 
-        // setting active window to None is to quit
-        state.active = ui::Window::None;
-        // Also make sure databse is flushed.
-        if let Err(err) = STORAGE.lock().unwrap().cache_flush() {
-            eprintln!("Cannot flush the storage db. Error: {err}", err = err);
+// This handler is fired when user press BACKSPACE key
+// backspace key will pop the last character from search query if pressed from searchbar
+// and if this key is pressed from somewhere else other than searchbar then will simply
+// move to previous window
+fn handle_backspace(state: &mut ui::State) {
+    match state.active {
+        ui::Window::Searchbar => {
+            state.search.0.pop();
+            state.search_edited();
         }
+        _ => moveto_prev_window(state),
+    }
+}
 
-        notifier.notify_all();
-        true
-    };
+// This handler is fired when use press SEARCH_SH_KEY
+// this will move the curson to the searchbar from which user can start to type the query
+fn activate_search(state: &mut ui::State) {
+    state.active = ui::Window::Searchbar;
+}
 
-    // This handler will fire up when user request to move between sections like musicbar, sidebar
-    // etc. Similar handler moveto_next_window / moveto_prev_window are not merged as these
-    // closures as these handlers are frequently called so avoid more branching
-    let moveto_next_window = || {
-        let mut state = state_original.lock().unwrap();
-        state.active = state.active.next();
-        notifier.notify_all();
-    };
+// This handler will be fired when user hits UP_ARROW or DOWN_ARROW key
+// UP_ARROW will set the direction to PREV and DOWN_ARROW to NEXT
+// for now, these key will only handle the moving of list
+// So, depending on the window which is currently active, this function will call
+// the respective handler which will advance the corersponding list
+fn handle_up_down(state: &mut ui::State, commands: &CommandSender, direction: HeadTo) {
+    let rows = match direction {
+        HeadTo::Prev => -1,
+        _ => 1,
+    };
+    match state.active {
+        ui::Window::Sidebar => advance_sidebar(state, direction),
+        ui::Window::Musicbar => scroll_list(state, commands, MIDDLE_MUSIC_INDEX, rows),
+        ui::Window::Playlistbar => scroll_list(state, commands, MIDDLE_PLAYLIST_INDEX, rows),
+        ui::Window::Artistbar => scroll_list(state, commands, MIDDLE_ARTIST_INDEX, rows),
+        ui::Window::Popup(ui::utils::REGION_PICKER_TITLE, _) => state.move_region_picker(rows),
+        ui::Window::Popup(ui::utils::SLEEP_PICKER_TITLE, _) => state.move_sleep_picker(rows),
+        // Up shows older query and Down the newer one
+        ui::Window::Searchbar => state.walk_search_history(matches!(direction, HeadTo::Prev)),
+        _ => match direction {
+            HeadTo::Next => moveto_next_window(state),
+            HeadTo::Prev => moveto_prev_window(state),
+            _ => unreachable!(),
+        },
+    }
+}
+
+fn start_search(state: &mut ui::State, commands: &CommandSender) {
+    let search_term = state.search.0.trim().to_string();
+
+    // return instantly if query is empty
+    if search_term.is_empty() {
+        return;
+    }
+    state.remember_search(&search_term);
+
+    // When prefiexed by the string as defined in config only show the specific result type
+    // respectively
+    if let Some(0) = search_term.find(&CONFIG.constants.search_by_type[0]) {
+        let search_term = search_term[CONFIG.constants.search_by_type[0].len() - 1..].to_string();
+        state.filled_source.0 = ui::MusicbarSource::Search(search_term);
+        refill_list(state, commands, MIDDLE_MUSIC_INDEX);
+    } else if let Some(0) = search_term.find(&CONFIG.constants.search_by_type[1]) {
+        let search_term = search_term[CONFIG.constants.search_by_type[1].len() - 1..].to_string();
+        state.filled_source.1 = ui::PlaylistbarSource::Search(search_term);
+        refill_list(state, commands, MIDDLE_PLAYLIST_INDEX);
+    } else if let Some(0) = search_term.find(&CONFIG.constants.search_by_type[2]) {
+        let search_term = search_term[&CONFIG.constants.search_by_type[2].len() - 1..].to_string();
+        state.filled_source.2 = ui::ArtistbarSource::Search(search_term);
+        refill_list(state, commands, MIDDLE_ARTIST_INDEX);
+    }
+    // If nothing of the prefix is defined then search for all type
+    else {
+        let search_term = search_term.to_string();
+        state.filled_source.0 = ui::MusicbarSource::Search(search_term.clone());
+        state.filled_source.1 = ui::PlaylistbarSource::Search(search_term.clone());
+        state.filled_source.2 = ui::ArtistbarSource::Search(search_term);
+        refill_list(state, commands, MIDDLE_PLAYLIST_INDEX);
+        refill_list(state, commands, MIDDLE_ARTIST_INDEX);
+        refill_list(state, commands, MIDDLE_MUSIC_INDEX);
+    }
+}
+
+fn fill_trending_music(state: &mut ui::State, commands: &CommandSender) {
+    state.filled_source.0 =
+        ui::MusicbarSource::Trending(state.trending.region.clone(), state.trending.category);
+    refill_list(state, commands, MIDDLE_MUSIC_INDEX);
+}
 
-    let moveto_prev_window = || {
-        let mut state = state_original.lock().unwrap();
-        state.active = state.active.prev();
-        notifier.notify_all();
+// Show trending of next category. Region stays the same
+fn cycle_trending_category(state: &mut ui::State, commands: &CommandSender) {
+    state.trending.category = state.trending.category.next();
+    fill_trending_music(state, commands);
+}
+
+// Show the channels of community group in artistbar and playlists in playlistbar. These are
+// already known from config so nothing is fetched
+fn fill_community_source(state: &mut ui::State, group_index: usize) {
+    let group = match state.community.groups().get(group_index) {
+        Some(group) => group.to_string(),
+        None => return,
+    };
+    let sources = state
+        .community
+        .sources
+        .iter()
+        .filter(|source| source.group == group);
+
+    let mut channels = Vec::new();
+    let mut playlists = Vec::new();
+    for source in sources {
+        match source.kind {
+            config::SourceKind::Channel => channels.push(fetcher::ArtistUnit {
+                name: source.name.clone(),
+                id: source.id.clone(),
+                ..Default::default()
+            }),
+            config::SourceKind::Playlist => playlists.push(fetcher::PlaylistUnit {
+                name: source.name.clone(),
+                id: source.id.clone(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    // List being fetched, if any, should not replace these lists
+    for index in [MIDDLE_PLAYLIST_INDEX, MIDDLE_ARTIST_INDEX] {
+        state.fill_generation[index] += 1;
+        state.pages[index] = ui::Pagination::default();
+    }
+    state.active = if channels.is_empty() {
+        ui::Window::Playlistbar
+    } else {
+        ui::Window::Artistbar
     };
+    state.artistbar.0 = channels;
+    state.playlistbar.0 = playlists;
+}
 
-    // This handler is fired when user press ESC key,
-    // if searchbar is active clear the content in search bar and move to next window
-    // if helpbar is active anway move to sidebar just to hide the help window
-    let handle_esc = || {
-        let mut state = state_original.lock().unwrap();
-        match state.active {
-            ui::Window::Searchbar | ui::Window::Popup(..) => {
-                state.search.0.clear();
-                drop_and_call!(state, moveto_next_window);
+// Add the focused artist or playlist to the default group of community and save it in config
+fn add_to_community(state: &mut ui::State) {
+    let (name, id, kind) = match state.active {
+        ui::Window::Artistbar => match state.artistbar.1.selected() {
+            Some(index) => {
+                let artist = &state.artistbar.0[index];
+                (&artist.name, &artist.id, config::SourceKind::Channel)
             }
-            ui::Window::BottomControl => {
-                drop_and_call!(state, moveto_next_window);
+            None => {
+                state.status = "Nothing selected..";
+                return;
             }
-            ui::Window::Sidebar
-            | ui::Window::Musicbar
-            | ui::Window::Playlistbar
-            | ui::Window::Artistbar => {
-                state.active = ui::Window::BottomControl;
-                notifier.notify_all();
+        },
+        ui::Window::Playlistbar => match state.playlistbar.1.selected() {
+            Some(index) => {
+                let playlist = &state.playlistbar.0[index];
+                (&playlist.name, &playlist.id, config::SourceKind::Playlist)
             }
-            ui::Window::None => {
-                unreachable!();
+            None => {
+                state.status = "Nothing selected..";
+                return;
             }
-        }
+        },
+        _ => return,
     };
 
-    // This handler is fired when user press BACKSPACE key
-    // backspace key will pop the last character from search query if pressed from searchbar
-    // and if this key is pressed from somewhere else other than searchbar then will simply
-    // move to previous window
-    let handle_backspace = || {
-        let mut state = state_original.lock().unwrap();
-        match state.active {
-            ui::Window::Searchbar => {
-                state.search.0.pop();
-                notifier.notify_all();
-            }
-            _ => drop_and_call!(state, moveto_prev_window),
-        }
+    if state
+        .community
+        .sources
+        .iter()
+        .any(|source| source.id == *id)
+    {
+        state.status = "Already in community..";
+        return;
+    }
+    let source = config::CommunitySource {
+        name: name.clone(),
+        id: id.clone(),
+        kind,
+        group: state.community.default_group.clone(),
     };
+    state.community.sources.push(source);
 
-    // This is fires when user press any character key
-    // this will simpley push the recived character in search query term and update state
-    // so can the added character becomes visible
-    let handle_search_input = |ch| {
-        state_original.lock().unwrap().search.0.push(ch);
-        notifier.notify_all();
+    state.status = match config::ConfigContainer::save_community(&state.community) {
+        Some(()) => "Added to community..",
+        // Still listed in this session
+        None => "Config save error..",
     };
+}
 
-    // This handler is fired when use press SEARCH_SH_KEY
-    // this will move the curson to the searchbar from which user can start to type the query
-    let activate_search = || {
-        let mut state = state_original.lock().unwrap();
-        state.active = ui::Window::Searchbar;
-        notifier.notify_all();
-    };
+fn fill_fav_music(state: &mut ui::State, commands: &CommandSender) {
+    state.filled_source.0 = ui::MusicbarSource::Favourates;
+    refill_list(state, commands, MIDDLE_MUSIC_INDEX);
+}
 
-    // This handler will be fired when user hits UP_ARROW or DOWN_ARROW key
-    // UP_ARROW will set the direction to PREV and DOWN_ARROW to NEXT
-    // for now, these key will only handle the moving of list
-    // So, depending on the window which is currently active, this closure will call
-    // the respective handler which will advance the corersponding list
-    let handle_up_down = |direction: HeadTo| {
-        let state = state_original.lock().unwrap();
-        match state.active {
-            ui::Window::Sidebar => drop_and_call!(state, advance_sidebar, direction),
-            ui::Window::Musicbar => drop_and_call!(state, advance_music_list, direction),
-            ui::Window::Playlistbar => drop_and_call!(state, advance_playlist_list, direction),
-            ui::Window::Artistbar => drop_and_call!(state, advance_artist_list, direction),
-            _ => match direction {
-                HeadTo::Next => drop_and_call!(state, moveto_next_window),
-                HeadTo::Prev => drop_and_call!(state, moveto_prev_window),
-                _ => unreachable!(),
-            },
-        }
-    };
+fn fill_fav_playlist(state: &mut ui::State, commands: &CommandSender) {
+    state.filled_source.1 = ui::PlaylistbarSource::Favourates;
+    refill_list(state, commands, MIDDLE_PLAYLIST_INDEX);
+}
 
-    let start_search = || {
-        let mut state = state_original.lock().unwrap();
-        let search_term = state.search.0.trim();
+fn fill_fav_artist(state: &mut ui::State, commands: &CommandSender) {
+    state.filled_source.2 = ui::ArtistbarSource::Favourates;
+    refill_list(state, commands, MIDDLE_ARTIST_INDEX);
+}
+
+// play next/previous song from queue
+fn change_track(state: &mut ui::State, direction: HeadTo) {
+    match direction {
+        HeadTo::Next => state.player.play_next(),
+        HeadTo::Prev => state.player.play_prev(),
+        HeadTo::Initial => unreachable!(),
+    }
+}
 
-        // return instantly if query is empty
-        if search_term.is_empty() {
+// navigating page is just moving the cursor of list by a page. List grows by itself as cursor
+// moves. Or changing the prev/next track
+fn handle_nav(state: &mut ui::State, commands: &CommandSender, direction: HeadTo) {
+    let target_index = match state.active {
+        ui::Window::Musicbar => MIDDLE_MUSIC_INDEX,
+        ui::Window::Playlistbar => MIDDLE_PLAYLIST_INDEX,
+        ui::Window::Artistbar => MIDDLE_ARTIST_INDEX,
+        ui::Window::BottomControl => {
+            // On reciving next/prev event when active window is bottom control
+            // It implied to change the track
+            return change_track(state, direction);
+        }
+        ui::Window::Searchbar | ui::Window::Sidebar | ui::Window::Popup(..) => {
+            // If none of above windows are active then nothing to navigate.
+            // Early return instead of initilizing `target_index`
             return;
         }
-        // When prefiexed by the string as defined in config only show the specific result type
-        // respectively
-        else if let Some(0) = search_term.find(&CONFIG.constants.search_by_type[0]) {
-            let search_term =
-                search_term[CONFIG.constants.search_by_type[0].len() - 1..].to_string();
-            state.fetched_page[0] = Some(0);
-            state.filled_source.0 = ui::MusicbarSource::Search(search_term);
-        } else if let Some(0) = search_term.find(&CONFIG.constants.search_by_type[1]) {
-            let search_term =
-                search_term[CONFIG.constants.search_by_type[1].len() - 1..].to_string();
-            state.fetched_page[1] = Some(0);
-            state.filled_source.1 = ui::PlaylistbarSource::Search(search_term);
-        } else if let Some(0) = search_term.find(&CONFIG.constants.search_by_type[2]) {
-            let search_term =
-                search_term[&CONFIG.constants.search_by_type[2].len() - 1..].to_string();
-            state.fetched_page[2] = Some(0);
-            state.filled_source.2 = ui::ArtistbarSource::Search(search_term);
-        }
-        // If nothing of the prefix is defined then search for all type
-        else {
-            let search_term = search_term.to_string();
-            state.fetched_page = [Some(0); 3];
-            state.filled_source.0 = ui::MusicbarSource::Search(search_term.clone());
-            state.filled_source.1 = ui::PlaylistbarSource::Search(search_term.clone());
-            state.filled_source.2 = ui::ArtistbarSource::Search(search_term);
-        }
-        notifier.notify_all();
+        ui::Window::None => unreachable!(),
     };
-
-    let fill_trending_music = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        state.fetched_page[MIDDLE_MUSIC_INDEX] =
-            Some(get_page(&state.fetched_page[MIDDLE_MUSIC_INDEX], direction));
-        state.filled_source.0 = ui::MusicbarSource::Trending;
-        notifier.notify_all();
-    };
-
-    let fill_community_source = || {
-        let mut state = state_original.lock().unwrap();
-        state.artistbar.0 = youtube_community_channels.clone();
-        state.active = ui::Window::Artistbar;
-        notifier.notify_all();
-    };
-
-    let fill_fav_music = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        state.filled_source.0 = ui::MusicbarSource::Favourates;
-        let page = get_page(&state.fetched_page[MIDDLE_MUSIC_INDEX], direction);
-        state.fetched_page[MIDDLE_MUSIC_INDEX] = Some(page);
-        notifier.notify_all();
+    let page_rows = CONFIG.constants.item_per_list as isize;
+    let rows = match direction {
+        HeadTo::Prev => -page_rows,
+        _ => page_rows,
     };
+    scroll_list(state, commands, target_index, rows);
+}
 
-    let fill_fav_playlist = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        state.filled_source.1 = ui::PlaylistbarSource::Favourates;
-        let page = get_page(&state.fetched_page[MIDDLE_PLAYLIST_INDEX], direction);
-        state.fetched_page[MIDDLE_PLAYLIST_INDEX] = Some(page);
-        notifier.notify_all();
-    };
+// Return true if sleep timer went off and application is quitting.
+// This is checked by painter every time it wakes up
+pub fn check_sleep_timer(state: &mut ui::State) -> bool {
+    if state.sleep_timer.is_none() {
+        return false;
+    }
 
-    let fill_fav_artist = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        state.filled_source.2 = ui::ArtistbarSource::Favourates;
-        let page = get_page(&state.fetched_page[MIDDLE_ARTIST_INDEX], direction);
-        state.fetched_page[MIDDLE_ARTIST_INDEX] = Some(page);
-        notifier.notify_all();
-    };
+    let should_quit = state.tick_sleep_timer();
+    should_quit && quit(state, false)
+}
 
-    let fill_music_from_playlist = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        if let ui::MusicbarSource::Playlist(playlist_id) = &state.filled_source.0 {
-            state.filled_source.0 = ui::MusicbarSource::Playlist(playlist_id.to_string());
-            state.fetched_page[MIDDLE_MUSIC_INDEX] =
-                Some(get_page(&state.fetched_page[MIDDLE_MUSIC_INDEX], direction));
-            notifier.notify_all();
-        }
-    };
+// TODO: Ask for conformation before downloading
+fn handle_download(state: &mut ui::State, commands: &CommandSender) {
+    let download_url;
+    if let Some(focused_index) = state.musicbar.1.selected() {
+        let music_id = &state.musicbar.0[focused_index].id;
+        download_url = format!("https://www.youtube.com/watch?v={}", music_id);
+    } else if let Some(focused_index) = state.playlistbar.1.selected() {
+        let playlist_id = &state.playlistbar.0[focused_index].id;
+        download_url = format!("https://www.youtube.com/playlist?list={}", playlist_id);
+    } else {
+        return;
+    }
 
-    let fill_music_from_artist = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        if let ui::MusicbarSource::Artist(artist_id) = &state.filled_source.0 {
-            state.filled_source.0 = ui::MusicbarSource::Artist(artist_id.to_string());
-            state.fetched_page[MIDDLE_MUSIC_INDEX] =
-                Some(get_page(&state.fetched_page[MIDDLE_MUSIC_INDEX], direction));
-            notifier.notify_all();
-        }
-    };
+    state.status = "Download started..";
+    state.active = ui::Window::Popup(
+        "Downloading...",
+        format!(
+            "Download of {} have an eye on your Music folder",
+            download_url
+        ),
+    );
+
+    // Communicator spawns the download and lets painter know once it finishes
+    state.downloads += 1;
+    commands.send(Command::Download(download_url)).ok();
+}
 
-    let fill_playlist_from_artist = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        if let ui::PlaylistbarSource::Artist(artist_id) = &state.filled_source.1 {
-            state.filled_source.1 = ui::PlaylistbarSource::Artist(artist_id.to_string());
-            state.fetched_page[MIDDLE_PLAYLIST_INDEX] = Some(get_page(
-                &state.fetched_page[MIDDLE_PLAYLIST_INDEX],
-                direction,
-            ));
-            notifier.notify_all();
+// If play is true it means also play the playlist
+// if is false then only expand the playlist and show url but do not play it
+fn select_playlist(state: &mut ui::State, commands: &CommandSender, play: bool) {
+    if let Some(selected_index) = state.playlistbar.1.selected() {
+        let playlist_id = state.playlistbar.0[selected_index].id.clone();
+        if play {
+            state.activate_playlist(&playlist_id);
+        } else {
+            let message = format!(
+                "Playlist url: https://youtu.be/playlist?list={}",
+                playlist_id
+            );
+            state.active = ui::Window::Popup("Info!", message);
         }
-    };
-
-    // play next/previous song from queue
-    let change_track = |direction: HeadTo| match direction {
-        HeadTo::Next => state_original.lock().unwrap().player.play_next(),
-        HeadTo::Prev => state_original.lock().unwrap().player.play_prev(),
-        HeadTo::Initial => unreachable!(),
-    };
+        state.filled_source.0 = ui::MusicbarSource::Playlist(playlist_id);
+        refill_list(state, commands, MIDDLE_MUSIC_INDEX);
+    }
+}
 
-    // navigating page is just changing to fetched_page value to next/prev value
-    // or changing the prev/next track
-    let handle_nav = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-        let target_index: usize;
-        match state.active {
-            ui::Window::Musicbar => target_index = MIDDLE_MUSIC_INDEX,
-            ui::Window::Playlistbar => target_index = MIDDLE_PLAYLIST_INDEX,
-            ui::Window::Artistbar => target_index = MIDDLE_ARTIST_INDEX,
-            ui::Window::BottomControl => {
-                // On reciving next/prev event when active window is bottom control
-                // It implied to change the track
-                return drop_and_call!(state, change_track, direction);
+fn select_music(state: &mut ui::State, play: bool) {
+    if let Some(selected_index) = state.musicbar.1.selected() {
+        let music = &state.musicbar.0[selected_index];
+        if play {
+            let music_id = music.id.clone();
+            state.play_music(&music_id);
+        } else {
+            let mut message = format!("Music url: https://youtu.be/{}", music.id);
+            if let Some(views) = music.views {
+                message.push_str(&format!("\nViews: {}", ui::utils::short_count(views)));
             }
-            ui::Window::Searchbar | ui::Window::Sidebar | ui::Window::Popup(..) => {
-                // If none of above windows are active then nothing to navigate.
-                // Early return instead of initilizing `target_index`
-                return;
+            if let Some(published) = music.published {
+                message.push_str(&format!("\nPublished: {}", ui::utils::time_ago(published)));
             }
-            ui::Window::None => unreachable!(),
+            state.active = ui::Window::Popup("Info!", message);
         }
-        let page = get_page(&state.fetched_page[target_index], direction);
-        state.fetched_page[target_index] = Some(page);
-        notifier.notify_all();
-    };
+    }
+}
 
-    let seek_forward = || {
-        state_original
-            .lock()
-            .unwrap()
-            .player
-            .seek_forward(CONFIG.constants.seek_forward_secs as f64)
-            .ok();
-        notifier.notify_all();
+fn change_volume(state: &mut ui::State, direction: HeadTo) {
+    let increase_by = match direction {
+        HeadTo::Next => CONFIG.constants.volume_step,
+        HeadTo::Prev => -CONFIG.constants.volume_step,
+        HeadTo::Initial => 0,
     };
 
-    let seek_backward = || {
-        state_original
-            .lock()
-            .unwrap()
-            .player
-            .seek_backward(CONFIG.constants.seek_backward_secs as f64)
-            .ok();
-        notifier.notify_all();
-    };
+    let res = state.player.change_volume(increase_by);
 
-    let handle_repeat = || {
-        let mut state = state_original.lock().unwrap();
-        state.player.repeat_nothing();
-        if state.playback_behaviour.repeat {
-            state.player.repeat_one();
-        } else {
-            state.player.repeat_playlist();
+    match res {
+        Some(vol) => {
+            state.playback_behaviour.volume = vol;
         }
-        state.playback_behaviour.repeat = !state.playback_behaviour.repeat;
-        notifier.notify_all();
-    };
-
-    let toggle_shuffle = || {
-        let mut state = state_original.lock().unwrap();
-        if state.playback_behaviour.shuffle {
-            state.player.unshuffle();
-        } else {
-            state.player.shuffle();
+        None => {
+            state.status = "Volume error..";
         }
-        state.playback_behaviour.shuffle = !state.playback_behaviour.shuffle;
-        notifier.notify_all();
     };
+}
 
-    let toggle_play = || {
-        state_original.lock().unwrap().toggle_pause();
-        notifier.notify_all();
+// HeadTo::Initial resets the speed to normal
+fn change_speed(state: &mut ui::State, direction: HeadTo) {
+    let res = match direction {
+        HeadTo::Next => state.player.change_speed(CONFIG.constants.speed_step),
+        HeadTo::Prev => state.player.change_speed(-CONFIG.constants.speed_step),
+        HeadTo::Initial => state.player.set_speed(1.0).then_some(1.0),
     };
 
-    let handle_download = || async {
-        let mut state = state_original.lock().unwrap();
-
-        // TODO: Ask for conformation before downloading
-        let mut command = tokio::process::Command::new("youtube-dl");
-        let download_url;
-        if let Some(focused_index) = state.musicbar.1.selected() {
-            let music_id = &state.musicbar.0[focused_index].id;
-            download_url = format!("https://www.youtube.com/watch?v={}", music_id);
-        } else if let Some(focused_index) = state.playlistbar.1.selected() {
-            let playlist_id = &state.playlistbar.0[focused_index].id;
-            download_url = format!("https://www.youtube.com/playlist?list={}", playlist_id);
-        } else {
-            return;
+    match res {
+        Some(speed) => {
+            state.playback_behaviour.speed = speed;
         }
-
-        state.status = "Download started..";
-        state.active = ui::Window::Popup(
-            "Downloading...",
-            format!(
-                "Download of {} have an eye on your Music folder",
-                download_url
-            ),
-        );
-        command.arg(download_url);
-
-        command
-            .stdin(std::process::Stdio::null())
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .args(&["--extract-audio", "--audio-format", &CONFIG.download.format])
-            .current_dir(&CONFIG.download.path)
-            .kill_on_drop(false);
-
-        std::mem::drop(state);
-
-        *download_counter.lock().unwrap() += 1;
-        let counter_clone = Arc::clone(&download_counter);
-
-        // Wait for 5 second just to make sure that command has finished executing.
-        // It usually donot take all those 5 seconds
-        // Anyway, download won't finish before 5 seconds
-        // Then just wait for command to finish by waiting for exit status
-        // decrease the download queue count
-        tokio::task::spawn(async move {
-            tokio::time::sleep(Duration::from_secs(5)).await;
-            command.status().await.unwrap();
-            *counter_clone.lock().unwrap() -= 1;
-        });
-    };
-
-    // If play is true it means also play the playlist
-    // if is false then only expand the playlist and show url but do not play it
-    let select_playlist = |play: bool| {
-        let mut state = state_original.lock().unwrap();
-        if let Some(selected_index) = state.playlistbar.1.selected() {
-            let playlist_id = state.playlistbar.0[selected_index].id.clone();
-            if play {
-                state.activate_playlist(&playlist_id);
-            } else {
-                let message = format!(
-                    "Playlist url: https://youtu.be/playlist?list={}",
-                    playlist_id
-                );
-                state.active = ui::Window::Popup("Info!", message);
-            }
-            state.filled_source.0 = ui::MusicbarSource::Playlist(playlist_id);
-            drop_and_call!(state, fill_music_from_playlist, HeadTo::Initial);
+        None => {
+            state.status = "Speed error..";
         }
     };
+}
 
-    let select_music = |play: bool| {
-        let mut state = state_original.lock().unwrap();
-        if let Some(selected_index) = state.musicbar.1.selected() {
-            let music_id = &state.musicbar.0[selected_index].id;
-            if play {
-                let music_id = music_id.clone();
-                state.play_music(&music_id);
-            } else {
-                let message = format!("Music url: https://youtu.be/{}", music_id);
-                state.active = ui::Window::Popup("Info!", message);
-                notifier.notify_all();
+fn handle_view(state: &mut ui::State, commands: &CommandSender) {
+    match state.active {
+        ui::Window::Playlistbar => select_playlist(state, commands, false),
+        ui::Window::Musicbar => select_music(state, false),
+        ui::Window::Artistbar => {}
+        _ => {}
+    }
+}
+
+fn handle_enter(state: &mut ui::State, commands: &CommandSender) {
+    match state.active {
+        ui::Window::Sidebar => {
+            let side_select = ui::SidebarOption::from(state.sidebar.selected().unwrap());
+
+            match side_select {
+                ui::SidebarOption::Trending => fill_trending_music(state, commands),
+                ui::SidebarOption::Liked => fill_fav_music(state, commands),
+                ui::SidebarOption::Saved => fill_fav_playlist(state, commands),
+                ui::SidebarOption::Following => fill_fav_artist(state, commands),
+                ui::SidebarOption::Search => activate_search(state),
+                ui::SidebarOption::Community(group_index) => {
+                    fill_community_source(state, group_index)
+                }
             }
         }
-    };
-
-    let change_volume = |direction: HeadTo| {
-        let mut state = state_original.lock().unwrap();
-
-        let increase_by = match direction {
-            HeadTo::Next => CONFIG.constants.volume_step,
-            HeadTo::Prev => CONFIG.constants.volume_step * -1,
-            HeadTo::Initial => 0,
-        };
-
-        let res = state.player.change_volume(increase_by);
+        ui::Window::Searchbar => start_search(state, commands),
 
-        match res {
-            Some(vol) => {
-                state.playback_behaviour.volume = vol;
-            }
-            None => {
-                state.status = "Volume error..";
-            }
-        };
+        // On enter play the music
+        ui::Window::Musicbar => select_music(state, true),
 
-        notifier.notify_all();
-    };
+        // On enter selection view the playlist content as well as play it
+        ui::Window::Playlistbar => select_playlist(state, commands, true),
 
-    let handle_view = || {
-        let state = state_original.lock().unwrap();
-        match state.active {
-            ui::Window::Playlistbar => {
-                drop_and_call!(state, select_playlist, false);
-            }
-            ui::Window::Musicbar => {
-                drop_and_call!(state, select_music, false);
+        ui::Window::Artistbar => {
+            if let Some(selected_index) = state.artistbar.1.selected() {
+                let artist_id = state.artistbar.0[selected_index].id.clone();
+                state.filled_source.0 = ui::MusicbarSource::Artist(artist_id.clone());
+                state.filled_source.1 = ui::PlaylistbarSource::Artist(artist_id);
+                refill_list(state, commands, MIDDLE_MUSIC_INDEX);
+                refill_list(state, commands, MIDDLE_PLAYLIST_INDEX);
             }
-            ui::Window::Artistbar => {}
-            _ => {}
         }
-    };
-
-    let handle_enter = || {
-        let mut state = state_original.lock().unwrap();
-        let active_window = state.active.clone();
-        match active_window {
-            ui::Window::Sidebar => {
-                let side_select =
-                    ui::SidebarOption::try_from(state.sidebar.selected().unwrap()).unwrap();
-
-                match side_select {
-                    ui::SidebarOption::Trending => {
-                        drop_and_call!(state, fill_trending_music, HeadTo::Initial);
-                    }
-                    ui::SidebarOption::YoutubeCommunity => {
-                        drop_and_call!(state, fill_community_source);
-                    }
-                    ui::SidebarOption::Liked => {
-                        drop_and_call!(state, fill_fav_music, HeadTo::Initial);
-                    }
-                    ui::SidebarOption::Saved => {
-                        drop_and_call!(state, fill_fav_playlist, HeadTo::Initial);
-                    }
-                    ui::SidebarOption::Following => {
-                        drop_and_call!(state, fill_fav_artist, HeadTo::Initial);
-                    }
-                    ui::SidebarOption::Search => drop_and_call!(state, activate_search),
-                }
-            }
-            ui::Window::Searchbar => {
-                drop_and_call!(state, start_search);
-            }
+        ui::Window::Popup(ui::utils::REGION_PICKER_TITLE, _) => {
+            state.pick_region();
+            fill_trending_music(state, commands);
+        }
+        ui::Window::Popup(ui::utils::SLEEP_PICKER_TITLE, _) => state.pick_sleep_timer(),
+        ui::Window::None | ui::Window::BottomControl | ui::Window::Popup(..) => {}
+    }
+}
 
-            // On enter play the music
-            ui::Window::Musicbar => drop_and_call!(state, select_music, true),
+// Start radio from the focused artist. Only works when artistbar is active
+fn start_artist_radio(state: &mut ui::State) {
+    if state.active != ui::Window::Artistbar {
+        return;
+    }
 
-            // On enter selection view the playlist content as well as play it
-            ui::Window::Playlistbar => drop_and_call!(state, select_playlist, true),
+    if let Some(selected_index) = state.artistbar.1.selected() {
+        let artist_id = state.artistbar.0[selected_index].id.clone();
+        state.artist_radio = Some(artist_id);
+    } else {
+        state.status = "Nothing selected..";
+    }
+}
 
-            ui::Window::Artistbar => {
-                if let Some(selected_index) = state.artistbar.1.selected() {
-                    let artist_id = state.artistbar.0[selected_index].id.clone();
-                    state.filled_source.0 = ui::MusicbarSource::Artist(artist_id.clone());
-                    state.filled_source.1 = ui::PlaylistbarSource::Artist(artist_id);
-                    std::mem::drop(state);
-                    fill_music_from_artist(HeadTo::Initial);
-                    fill_playlist_from_artist(HeadTo::Initial);
-                }
-            }
-            ui::Window::None | ui::Window::BottomControl | ui::Window::Popup(..) => {}
-        }
-    };
+fn handle_favourates(state: &mut ui::State, add: bool) {
+    state.status = "Processing..";
 
-    let handle_favourates = |add: bool| {
-        let mut state = state_original.lock().unwrap();
-
-        state.status = "Processing..";
-
-        match state.active {
-            ui::Window::Musicbar => {
-                if let Some(selected_index) = state.musicbar.1.selected() {
-                    // Why clone? Used unsafe to bypass borrow as immutable and mutable at same time error.
-                    // yeah. I am sure this is fine (up until now).
-                    let selected_music =
-                        &state.musicbar.0[selected_index] as *const fetcher::MusicUnit;
-                    if add {
-                        state.add_music_to_favourates(unsafe { &*selected_music });
-                    } else {
-                        state.remove_music_from_favourates(unsafe { &*selected_music });
-                    }
+    match state.active {
+        ui::Window::Musicbar => {
+            if let Some(selected_index) = state.musicbar.1.selected() {
+                let selected_music = state.musicbar.0[selected_index].clone();
+                if add {
+                    state.add_music_to_favourates(&selected_music);
                 } else {
-                    state.status = "Nothing selected..";
+                    state.remove_music_from_favourates(&selected_music);
                 }
+            } else {
+                state.status = "Nothing selected..";
             }
+        }
 
-            ui::Window::Playlistbar => {
-                if let Some(selected_index) = state.playlistbar.1.selected() {
-                    let selected_playlist =
-                        &state.playlistbar.0[selected_index] as *const fetcher::PlaylistUnit;
-                    if add {
-                        state.add_playlist_to_favourates(unsafe { &*selected_playlist });
-                    } else {
-                        state.remove_playlist_from_favourates(unsafe { &*selected_playlist });
-                    }
+        ui::Window::Playlistbar => {
+            if let Some(selected_index) = state.playlistbar.1.selected() {
+                let selected_playlist = state.playlistbar.0[selected_index].clone();
+                if add {
+                    state.add_playlist_to_favourates(&selected_playlist);
                 } else {
-                    state.status = "Nothing selected..";
+                    state.remove_playlist_from_favourates(&selected_playlist);
                 }
+            } else {
+                state.status = "Nothing selected..";
             }
+        }
 
-            ui::Window::Artistbar => {
-                if let Some(selected_index) = state.artistbar.1.selected() {
-                    let selected_artist =
-                        &state.artistbar.0[selected_index] as *const fetcher::ArtistUnit;
-                    if add {
-                        state.add_artist_to_favourates(unsafe { &*selected_artist });
-                    } else {
-                        state.remove_artist_from_favourates(unsafe { &*selected_artist });
-                    }
+        ui::Window::Artistbar => {
+            if let Some(selected_index) = state.artistbar.1.selected() {
+                let selected_artist = state.artistbar.0[selected_index].clone();
+                if add {
+                    state.add_artist_to_favourates(&selected_artist);
                 } else {
-                    state.status = "Nothing selected..";
+                    state.remove_artist_from_favourates(&selected_artist);
                 }
+            } else {
+                state.status = "Nothing selected..";
             }
-            _ => {}
         }
+        _ => {}
+    }
+}
 
-        notifier.notify_all();
+// Called by painter for every event sent by `event_listener`.
+// Return false if user asked to quit the application
+pub fn handle_event(state: &mut ui::State, event: Event, commands: &CommandSender) -> bool {
+    let key = match event {
+        Event::Key(key) => key,
+        // Painter will repaint anyway and layout is updated on resize
+        Event::Resize(..) | Event::Mouse(..) => return true,
     };
+    let is_with_control = key.modifiers.contains(KeyModifiers::CONTROL);
 
-    'listener_loop: loop {
-        if event::poll(Duration::from_millis(CONFIG.constants.refresh_rate)).unwrap() {
-            match event::read().unwrap() {
-                Event::Key(key) => {
-                    let is_with_control = key.modifiers.contains(KeyModifiers::CONTROL);
-
-                    match key.code {
-                        KeyCode::Down | KeyCode::PageDown => {
-                            handle_up_down(HeadTo::Next);
-                        }
-                        KeyCode::Up | KeyCode::PageUp => {
-                            handle_up_down(HeadTo::Prev);
-                        }
-                        KeyCode::Right | KeyCode::Tab => {
-                            moveto_next_window();
-                        }
-                        KeyCode::Left | KeyCode::BackTab => {
-                            moveto_prev_window();
-                        }
-                        KeyCode::Esc => {
-                            handle_esc();
-                        }
-                        KeyCode::Enter => {
-                            handle_enter();
-                        }
-                        KeyCode::Backspace | KeyCode::Delete => {
-                            handle_backspace();
-                        }
-                        KeyCode::Char(ch) => {
-                            /* If searchbar is active register every char key as input term */
-                            if state_original.lock().unwrap().active == ui::Window::Searchbar {
-                                handle_search_input(ch);
-                            }
-                            // Now as this is not the input, call the shortcuts action if this key
-                            // is defined in shortcuts
-                            else if ch == CONFIG.shortcut_keys.start_search {
-                                activate_search();
-                            } else if ch == CONFIG.shortcut_keys.toggle_play {
-                                toggle_play();
-                            } else if ch == CONFIG.shortcut_keys.repeat {
-                                handle_repeat();
-                            } else if ch == CONFIG.shortcut_keys.suffle {
-                                toggle_shuffle();
-                            } else if ch == CONFIG.shortcut_keys.forward {
-                                seek_forward();
-                            } else if ch == CONFIG.shortcut_keys.backward {
-                                seek_backward();
-                            } else if ch == CONFIG.shortcut_keys.view {
-                                handle_view();
-                            } else if ch == CONFIG.shortcut_keys.favourates_add {
-                                handle_favourates(true);
-                            } else if ch == CONFIG.shortcut_keys.favourates_remove {
-                                handle_favourates(false);
-                            } else if ch == CONFIG.shortcut_keys.prev {
-                                if is_with_control {
-                                    change_track(HeadTo::Prev);
-                                } else {
-                                    handle_nav(HeadTo::Prev);
-                                }
-                            } else if ch == CONFIG.shortcut_keys.next {
-                                if is_with_control {
-                                    change_track(HeadTo::Next);
-                                } else {
-                                    handle_nav(HeadTo::Next);
-                                }
-                            } else if ch == CONFIG.shortcut_keys.download && is_with_control {
-                                handle_download().await;
-                            } else if ch == CONFIG.shortcut_keys.vol_increase {
-                                change_volume(HeadTo::Next);
-                            } else if ch == CONFIG.shortcut_keys.vol_decrease {
-                                change_volume(HeadTo::Prev);
-                            } else if ch == CONFIG.shortcut_keys.quit && is_with_control {
-                                let force_quit = key.modifiers.contains(KeyModifiers::ALT);
-                                if quit(force_quit) {
-                                    break 'listener_loop;
-                                }
-                            }
-                        }
-                        _ => {}
-                    }
+    match key.code {
+        KeyCode::Down | KeyCode::PageDown => {
+            handle_up_down(state, commands, HeadTo::Next);
+        }
+        KeyCode::Up | KeyCode::PageUp => {
+            handle_up_down(state, commands, HeadTo::Prev);
+        }
+        // Tab in search box takes the next completion if there is any
+        KeyCode::Tab if state.active == ui::Window::Searchbar && state.complete_search() => {}
+        KeyCode::Right | KeyCode::Tab => {
+            moveto_next_window(state);
+        }
+        KeyCode::Left | KeyCode::BackTab => {
+            moveto_prev_window(state);
+        }
+        KeyCode::Esc => {
+            handle_esc(state);
+        }
+        KeyCode::Enter => {
+            handle_enter(state, commands);
+        }
+        KeyCode::Backspace | KeyCode::Delete => {
+            handle_backspace(state);
+        }
+        KeyCode::Char(ch) => {
+            /* If searchbar is active register every char key as input term */
+            if state.active == ui::Window::Searchbar {
+                state.search.0.push(ch);
+                state.search_edited();
+            }
+            // Digits typed in sleep timer picker are the minutes to sleep after
+            else if ch.is_ascii_digit()
+                && matches!(
+                    state.active,
+                    ui::Window::Popup(ui::utils::SLEEP_PICKER_TITLE, _)
+                )
+            {
+                state.type_sleep_minutes(ch);
+            }
+            // Now as this is not the input, call the shortcuts action if this key
+            // is defined in shortcuts
+            else if ch == CONFIG.shortcut_keys.start_search {
+                activate_search(state);
+            } else if ch == CONFIG.shortcut_keys.toggle_play {
+                state.toggle_pause();
+            } else if ch == CONFIG.shortcut_keys.repeat {
+                state.cycle_repeat();
+            } else if ch == CONFIG.shortcut_keys.suffle {
+                state.toggle_shuffle();
+            } else if ch == CONFIG.shortcut_keys.artist_radio {
+                start_artist_radio(state);
+            } else if ch == CONFIG.shortcut_keys.autoplay {
+                state.toggle_autoplay();
+            } else if ch == CONFIG.shortcut_keys.loudness {
+                let mode = state.playback_behaviour.loudness.next();
+                state.set_loudness_mode(mode);
+            } else if ch == CONFIG.shortcut_keys.audio_preset {
+                state.cycle_audio_preset();
+            } else if ch == CONFIG.shortcut_keys.sleep_timer {
+                state.show_sleep_picker();
+            } else if ch == CONFIG.shortcut_keys.forward {
+                state.seek(CONFIG.constants.seek_forward_secs as f64);
+            } else if ch == CONFIG.shortcut_keys.backward {
+                state.seek(-(CONFIG.constants.seek_backward_secs as f64));
+            } else if ch == CONFIG.shortcut_keys.next_chapter {
+                state.change_chapter(true);
+            } else if ch == CONFIG.shortcut_keys.prev_chapter {
+                state.change_chapter(false);
+            } else if ch == CONFIG.shortcut_keys.chapters {
+                state.show_chapters();
+            } else if ch == CONFIG.shortcut_keys.video {
+                state.toggle_video();
+            } else if ch == CONFIG.shortcut_keys.lyrics {
+                state.toggle_lyrics();
+            } else if ch == CONFIG.shortcut_keys.trending_category {
+                cycle_trending_category(state, commands);
+            } else if ch == CONFIG.shortcut_keys.trending_region {
+                state.show_region_picker();
+            } else if ch == CONFIG.shortcut_keys.community_add {
+                add_to_community(state);
+            } else if ch == CONFIG.shortcut_keys.view {
+                handle_view(state, commands);
+            } else if ch == CONFIG.shortcut_keys.favourates_add {
+                handle_favourates(state, true);
+            } else if ch == CONFIG.shortcut_keys.favourates_remove {
+                handle_favourates(state, false);
+            } else if ch == CONFIG.shortcut_keys.prev {
+                if is_with_control {
+                    change_track(state, HeadTo::Prev);
+                } else {
+                    handle_nav(state, commands, HeadTo::Prev);
                 }
-                Event::Resize(..) => {
-                    // just update the layout
-                    notifier.notify_all();
+            } else if ch == CONFIG.shortcut_keys.next {
+                if is_with_control {
+                    change_track(state, HeadTo::Next);
+                } else {
+                    handle_nav(state, commands, HeadTo::Next);
                 }
-                Event::Mouse(..) => {}
+            } else if ch == CONFIG.shortcut_keys.download && is_with_control {
+                handle_download(state, commands);
+            } else if ch == CONFIG.shortcut_keys.vol_increase {
+                change_volume(state, HeadTo::Next);
+            } else if ch == CONFIG.shortcut_keys.vol_decrease {
+                change_volume(state, HeadTo::Prev);
+            } else if ch == CONFIG.shortcut_keys.speed_up {
+                change_speed(state, HeadTo::Next);
+            } else if ch == CONFIG.shortcut_keys.speed_down {
+                change_speed(state, HeadTo::Prev);
+            } else if ch == CONFIG.shortcut_keys.speed_reset {
+                change_speed(state, HeadTo::Initial);
+            } else if ch == CONFIG.shortcut_keys.quit && is_with_control {
+                let force_quit = key.modifiers.contains(KeyModifiers::ALT);
+                return !quit(state, force_quit);
             }
-        } else {
-            notifier.notify_all();
         }
+        _ => {}
     }
+
+    true
 }
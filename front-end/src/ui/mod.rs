@@ -1,16 +1,20 @@
 pub mod event;
+#[cfg(unix)]
+mod mpv_ipc;
+mod queue;
 mod utils;
-use std::sync::Condvar;
+use std::sync::mpsc;
 use tui::{backend::CrosstermBackend, Terminal};
 // These are the imports also used in __utils.rs__ so make this import shareable
 mod shared_import {
     pub use fetcher;
+    #[cfg(feature = "libmpv")]
     pub use libmpv;
     pub use serde::{Deserialize, Serialize};
     pub use std::convert::{From, Into, TryFrom, TryInto};
     pub use std::{
-        sync::{Arc, Mutex},
-        time::Duration,
+        collections::{HashMap, HashSet},
+        time::{Duration, Instant},
     };
     pub use tui::{
         backend::Backend,
@@ -76,7 +80,7 @@ pub struct MainLayout {
 // TODO: Instead of having seperate struct to hold SideBar Rect define Rect directly in MainLayout
 // So that this struct is removed and type of `MainLayout::sidebar` is Rect
 pub struct SideBar {
-    layout: [Rect; 2],
+    layout: [Rect; 3],
 }
 
 // --------------------------------------
@@ -145,6 +149,7 @@ pub struct Position {
     pub search: Rect,
     pub status: Rect,
     pub shortcut: Rect,
+    pub community: Rect,
     pub music: Rect,
     pub playlist: Rect,
     pub artist: Rect,
@@ -157,14 +162,20 @@ pub struct Position {
 // 1) Initilize the terminal backend
 // 2) Get the layout of the ui
 // 3) print content in ui
-// 4) Run a loop waiting for state variable to change
-// if user asks to quit the app(denoted by setting active window to None) -> Quit,
+// 4) Run a loop waiting for message in `inbox`
+// Painter is the only owner of the state. User input and fetched response are both sent here as
+// `Message` and are handled in this same loop so nothing else needs to lock the state.
+// if user asks to quit the app -> Quit,
 // else -> Update the ui
-// Ui is always updated when notified. No checkes are done to weather the ui is really updated or
-// not as algorithms defined in ternial backend is responsible for such checks.
-// Ui is also updated in every REFRESH_RATE specified which will then sync the states like
-// played duration to the ui. Also see documentation in __event.rs__ file
-pub fn draw_ui(state: &mut Arc<Mutex<State>>, cvar: &mut Arc<Condvar>) {
+// Ui is always updated after handling the messages. No checkes are done to weather the ui is
+// really updated or not as algorithms defined in ternial backend is responsible for such checks.
+// Ui is also updated in every REFRESH_RATE specified (on `Message::Tick`) which will then sync the
+// states like played duration to the ui. Also see documentation in __event.rs__ file
+pub fn draw_ui(
+    mut state: State,
+    inbox: mpsc::Receiver<Message>,
+    commands: crate::communicator::CommandSender,
+) {
     let mut stdout = std::io::stdout();
     execute!(stdout, EnterAlternateScreen).expect("Failed to enter alternate screen");
     terminal::enable_raw_mode().expect("Faild to enable raw mode");
@@ -181,11 +192,9 @@ pub fn draw_ui(state: &mut Arc<Mutex<State>>, cvar: &mut Arc<Condvar>) {
 
     let mut previous_dimension: Rect = Rect::default();
     let mut position = Position::caclulate(&previous_dimension);
-    let mut paint_ui = || {
+    let mut paint_ui = |state: &mut State| {
         terminal
             .draw(|screen| {
-                let mut state_unlocked = state.lock().unwrap();
-
                 // As screen size doesn't change that often (is chaged when terminal window is
                 // resized) so it is unnecessary to calcuate position for components in every draw
                 // loop. Calculate once and recalculate when window size change
@@ -195,71 +204,123 @@ pub fn draw_ui(state: &mut Arc<Mutex<State>>, cvar: &mut Arc<Condvar>) {
                     previous_dimension = current_dimension;
                 }
 
-                screen.render_widget(TopLayout::get_statusbox(&state_unlocked), position.status);
-                screen.render_widget(TopLayout::get_searchbox(&state_unlocked), position.search);
+                screen.render_widget(TopLayout::get_statusbox(state), position.status);
+                screen.render_widget(TopLayout::get_searchbox(state), position.search);
+                // Cursor of sidebar runs through shortcuts and then the community groups. So each
+                // list is given only the part of cursor that falls in it
+                let selected = state.sidebar.selected();
+                let mut shortcut_state = ListState::default();
+                shortcut_state.select(selected.filter(|index| *index < utils::SIDEBAR_LIST_COUNT));
                 screen.render_stateful_widget(
-                    SideBar::get_shortcuts(&state_unlocked),
+                    SideBar::get_shortcuts(state),
                     position.shortcut,
-                    &mut state_unlocked.sidebar,
+                    &mut shortcut_state,
+                );
+                let mut community_state = ListState::default();
+                community_state.select(
+                    selected.and_then(|index| index.checked_sub(utils::SIDEBAR_LIST_COUNT)),
                 );
-
-                // each of below three state keeps data as reference to prevent unnecessaru copy
-                // i.e they holds immutable reference to internal field of state variable so state
-                // is supposed to be borrowed immutable
-                // Again, as render_stateful_widget takes state of widget as mutable reference
-                // which is again inside our state variable so it becomes necessary to have two
-                // reference (one immutable and one mutable) at once
-                // One first thought is to wrap inside some cell but as this loop keeps running in
-                // short time interval copying anything for that purpose would be consuming more
-                // cpu. And it may be good time to play with unsafe
-                let state_ptr = &mut state_unlocked as *mut std::sync::MutexGuard<'_, State<'_>>;
-                let (mut music_state, mut playlist_state, mut artist_state);
-                unsafe {
-                    music_state = &mut (*state_ptr).musicbar.1;
-                    playlist_state = &mut (*state_ptr).playlistbar.1;
-                    artist_state = &mut (*state_ptr).artistbar.1;
-                }
-
-                let music_table = MiddleLayout::get_music_container(&mut state_unlocked);
-                screen.render_stateful_widget(music_table, position.music, &mut music_state);
-                let playlist_table = MiddleBottom::get_playlist_container(&mut state_unlocked);
                 screen.render_stateful_widget(
-                    playlist_table,
-                    position.playlist,
-                    &mut playlist_state,
+                    SideBar::get_community(state),
+                    position.community,
+                    &mut community_state,
                 );
-                let artist_table = MiddleBottom::get_artist_container(&mut state_unlocked);
-                screen.render_stateful_widget(artist_table, position.artist, &mut artist_state);
-
-                state_unlocked.refresh_mpv_status();
 
-                screen.render_widget(
-                    BottomLayout::get_status_bar(&state_unlocked),
-                    position.music_info,
+                // each of below three table keeps data as reference to prevent unnecessary copy
+                // while render_stateful_widget takes state of same table as mutable reference. So
+                // both are borrowed together from different field of state
+                let (music_table, music_state) = MiddleLayout::get_music_container(
+                    &state.active,
+                    &state.filled_source.0,
+                    &state.pages[event::MIDDLE_MUSIC_INDEX],
+                    &mut state.musicbar,
+                );
+                screen.render_stateful_widget(music_table, position.music, music_state);
+                let (playlist_table, playlist_state) = MiddleBottom::get_playlist_container(
+                    &state.active,
+                    &state.pages[event::MIDDLE_PLAYLIST_INDEX],
+                    &mut state.playlistbar,
                 );
-                screen.render_widget(
-                    BottomLayout::get_icons_set(&state_unlocked),
-                    position.bottom_icons,
+                screen.render_stateful_widget(playlist_table, position.playlist, playlist_state);
+                let (artist_table, artist_state) = MiddleBottom::get_artist_container(
+                    &state.active,
+                    &state.pages[event::MIDDLE_ARTIST_INDEX],
+                    &mut state.artistbar,
                 );
+                screen.render_stateful_widget(artist_table, position.artist, artist_state);
+
+                if state.lyrics.visible {
+                    let lyrics_area = position.playlist.union(position.artist);
+                    screen.render_widget(widgets::Clear, lyrics_area);
+                    screen.render_widget(
+                        MiddleBottom::get_lyrics_pane(state, lyrics_area.height),
+                        lyrics_area,
+                    );
+                }
+
+                screen.render_widget(BottomLayout::get_status_bar(state), position.music_info);
+                screen.render_widget(BottomLayout::get_icons_set(state), position.bottom_icons);
+
+                // Completions are drawn over whatever is below the search box
+                if state.active == Window::Searchbar {
+                    let count = state.search_completions().len() as u16;
+                    if count > 0 {
+                        let below = position.search.y + position.search.height;
+                        let area = Rect {
+                            y: below,
+                            // 2 rows are taken by border
+                            height: (count + 2).min(screen.size().height.saturating_sub(below)),
+                            ..position.search
+                        };
+                        let mut completion_state = ListState::default();
+                        completion_state.select(state.completion_pos());
+                        screen.render_widget(widgets::Clear, area);
+                        screen.render_stateful_widget(
+                            TopLayout::get_search_completions(state),
+                            area,
+                            &mut completion_state,
+                        );
+                    }
+                }
 
                 // Sho this popup at last after everything else is drawn.
                 // This makes sure that background is not empty and user can
                 // see some things like progress of music player
-                if let Window::Popup(title, ref content) = state_unlocked.active {
+                if let Window::Popup(title, ref content) = state.active {
                     utils::show_pupop_text(screen, [title, content], &position.popup);
                 }
             })
             .unwrap();
     };
-    paint_ui();
 
     'reactor: loop {
-        // Use if instead of match because if will drop the mutex while going to else branch
-        // but match keeps locking the mutex until match expression finished
-        if cvar.wait(state.lock().unwrap()).unwrap().active == Window::None {
+        state.refresh_mpv_status();
+        // Refreshing may have found something that should be fetched
+        crate::communicator::request_pending(&mut state, &commands);
+        paint_ui(&mut state);
+
+        // Both event listener and communicator stops once painter is gone so this is not expected
+        // to fail but if it does there is nothing to paint anyway
+        let message = match inbox.recv() {
+            Ok(message) => message,
+            Err(_) => break 'reactor,
+        };
+        // Handle all the messages that are already waiting before painting again so that painter
+        // do not lag behind when messages come quicker than it can paint
+        for message in std::iter::once(message).chain(inbox.try_iter()) {
+            match message {
+                Message::Input(input) => {
+                    if !event::handle_event(&mut state, input, &commands) {
+                        break 'reactor;
+                    }
+                }
+                Message::Tick => {}
+                Message::Fetched(response) => response.apply(&mut state),
+            }
+        }
+
+        if event::check_sleep_timer(&mut state) {
             break 'reactor;
-        } else {
-            paint_ui();
         }
     }
 
@@ -277,14 +338,17 @@ pub fn draw_ui(state: &mut Arc<Mutex<State>>, cvar: &mut Arc<Condvar>) {
         .unwrap_or_else(|_| eprintln!("Failed to show cursor. Try: stty sane"));
 }
 
+// Options of sidebar in the order they are listed. Shortcuts are followed by the groups of
+// community sources. See `config::Community`
 #[derive(Clone)]
 pub enum SidebarOption {
-    Trending = 0,
-    YoutubeCommunity = 1,
-    Liked = 2,
-    Saved = 3,
-    Following = 4,
-    Search = 5,
+    Trending,
+    Liked,
+    Saved,
+    Following,
+    Search,
+    // Index of group in `config::Community::groups`
+    Community(usize),
 }
 
 #[derive(PartialEq, Clone)]
@@ -307,12 +371,17 @@ pub struct BottomState {
     // false in Some means music is paused
     // None means playing nothing. eg: At the start of program
     playing: Option<(String, bool)>,
+    // Playing track is a live stream. It has no duration and cannot be seeked
+    live: bool,
+    // Id of music that were live when listed. Player may still know the duration of these
+    known_live: HashSet<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MusicbarSource {
     Search(String),
-    Trending,
+    // Region and category of trending
+    Trending(String, fetcher::TrendingCategory),
     RecentlyPlayed,
     Favourates,
     Playlist(String),
@@ -332,17 +401,220 @@ pub enum ArtistbarSource {
     Favourates,
 }
 
+// Pages of the music/playlist/artist list. List grows as the cursor gets near the end of it by
+// fetching the next page (or previous one if earlier pages were dropped) in background. Pages are
+// counted from 0 but shown from 1 in the title of the list as "page 3/5" when last page is known
+// or as "page 3+" when it is not
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pagination {
+    // First and last page currently in the list. None if list is not filled yet
+    pub loaded: Option<(usize, usize)>,
+    // Page being fetched. Response is always for this page as response of older request is
+    // dropped. See `State::fill_generation`
+    pub requested: Option<usize>,
+    // Last page that have some result. None until end of result is reached
+    pub last: Option<usize>,
+    // Page to fetch right after the requested one arrived as cursor is still near the edge of
+    // list. Taken by communicator. See `Pagination::follow_up`
+    pub follow_up: Option<usize>,
+}
+
+// Stage of resolving the stream of next item in the player queue
+#[derive(Debug, Clone, PartialEq)]
+pub enum Prefetch {
+    // Nothing to resolve. Either next item is already resolved or is not a youtube url
+    Idle,
+    // Item at `position` of player queue should be resolved. This is set by painter when track
+    // changes and is picked by communicator. `attempt` is the number of failed attempt so far
+    Request {
+        position: i64,
+        video_id: String,
+        attempt: u8,
+    },
+    // Communicator is resolving the stream
+    Pending,
+}
+
+// Player resolves the youtube url with ytdl hook only when it starts to play that item which
+// gives few seconds of silence between tracks. To avoid this, direct stream url of next item in
+// queue is resolved while current one plays and next entry of queue is replaced with that url so
+// that mpv can prefetch it (prefetch-playlist do not work for ytdl urls) and play gapless.
+pub struct PrefetchState {
+    // Position in player queue that was playing when last checked
+    playlist_pos: Option<i64>,
+    pub request: Prefetch,
+    // Streams resolved by us keyed by stream url. Direct stream url do not carry title with it so
+    // media-title would only show the url. So title (and id) is taken from here
+    resolved: HashMap<String, fetcher::AudioStream>,
+}
+
+// Keeps the gain of tracks when loudness mode is `TrackGain`.
+// Loudness of track is fetched from server once that track starts playing unless it is already
+// known while resolving the stream. See `PrefetchState`
+pub struct TrackGainState {
+    // Video id of the track whose gain is currently applied
+    applied_for: Option<String>,
+    // Video id of the track whose loudness should be fetched. Set by painter and taken by
+    // communicator
+    pub request: Option<String>,
+    // Video id whose loudness was last requested so that same track is not requested again in
+    // every refresh while it is being fetched
+    requested_for: Option<String>,
+    // Loudness (in dB) of the playing track and the ones fetched after it. None if server did not
+    // report the loudness
+    known: HashMap<String, Option<f64>>,
+}
+
+// When the sleep timer should go off
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SleepAt {
+    // At given instant. Second member is the number of minutes the timer was set for
+    Time(Instant, u64),
+    // When the track playing at given queue position ends
+    EndOfTrack(i64),
+    // When the last track of queue ends. Holds the queue position of last track once it starts
+    // playing so that timer goes off when player moves away from it, wrapping to first track when
+    // repeating the queue or going idle otherwise
+    EndOfQueue(Option<i64>),
+}
+
+// Sleep timer is checked every time painter wakes up. When the timer is about to go
+// off (within `Playback::sleep_fade_secs`), volume is decreased gradually and once it goes off
+// playback is paused and volume is restored to what it was before fading.
+pub struct SleepTimer {
+    pub at: SleepAt,
+    // Volume before fading was started. None if not yet fading
+    fading_from: Option<u8>,
+}
+
+// Chapters of currently playing track. Taken from player when it knows them (ytdl hook extracts
+// them when available) or else parsed from the timestamps in description of the video
+pub struct ChapterState {
+    // Path of the track these chapters belong to
+    path: Option<String>,
+    pub list: Vec<fetcher::Chapter>,
+    // Video id whose description should be fetched. Set by painter and taken by communicator
+    pub request: Option<String>,
+    // So that description is fetched only once per track
+    requested: bool,
+}
+
+// Video output of player. Stream resolved by us is audio only and ytdl hook also picks audio only
+// stream while video is off. So the playing track is loaded again from youtube url when video is
+// turned on. See `State::toggle_video`
+pub struct VideoState {
+    pub enabled: bool,
+    // Path of the track video was turned on for. Video is turned off again once some other track
+    // starts. None when video should stay on. See `Playback::video_current_track_only`
+    enabled_for: Option<String>,
+    // (new path, old path) of the track being loaded again with video. `start` option of player
+    // is set to resume from same time and is reset once the new one is loaded
+    reloading: Option<(String, String)>,
+}
+
+// Segments of currently playing track that should be skipped. See `config::SegmentSkip`
+pub struct SegmentSkipState {
+    // Path of the track these segments belong to
+    path: Option<String>,
+    pub list: Vec<fetcher::SkipSegment>,
+    // Video id whose segments should be fetched. Set by painter and taken by communicator
+    pub request: Option<String>,
+    // So that segments are fetched only once per track
+    requested: bool,
+    // Index of segments already skipped. Those are not skipped again if user seeks back into them
+    skipped: HashSet<usize>,
+}
+
+// Lyrics of currently playing track shown in place of playlist and artist pane when visible.
+// Taken from lrc file beside the downloaded track if there is one or else from captions of video
+pub struct LyricsState {
+    pub visible: bool,
+    // Path of the track these lyrics belong to
+    path: Option<String>,
+    pub lines: Vec<fetcher::lyrics::LyricLine>,
+    // Video id whose captions should be fetched. Set by painter and taken by communicator
+    pub request: Option<String>,
+    // Lyrics are looked up only once per track. Stays true even if nothing is found
+    requested: bool,
+    // Captions are asked and not yet received. So that pane can tell lyrics are being fetched
+    fetching: bool,
+}
+
+// Completion of query being typed in search box. Completions are the previous queries starting
+// with typed text followed by the suggestions from server. Tab cycles through these while Up/Down
+// walks through all the previous queries. See `State::complete_search`
+pub struct SearchAssist {
+    // Queries searched before, latest first. Loaded from local database at start
+    history: Vec<String>,
+    // Suggestions from server for `typed`
+    suggestions: Vec<String>,
+    // Text as typed by user. Search box may instead be showing a completion or previous query
+    typed: String,
+    // Index in history shown in search box while walking with Up/Down
+    history_pos: Option<usize>,
+    // Index in completions shown in search box while cycling with Tab
+    completion_pos: Option<usize>,
+    // When `typed` was last changed if it's suggestions are not yet asked for. Asked once it stays
+    // same for a while. See `State::take_suggestions_request`
+    edited_at: Option<Instant>,
+}
+
+// Region and category of trending to show when trending is selected from sidebar. Region starts
+// from `Constants::region` of config and can be picked from `config::TRENDING_REGIONS`
+pub struct TrendingState {
+    pub region: String,
+    pub category: fetcher::TrendingCategory,
+    // Index of region under cursor while region picker is shown
+    picker: usize,
+}
+
+// Playlist being added to the queue page by page. Playback starts as soon as the first page
+// arrives and the rest of pages are appended as they arrive so huge playlist do not keep user
+// waiting. See `State::use_playlist_page`
+pub struct PlaylistFeed {
+    pub id: String,
+    // Page to be fetched next. Set by painter and taken by communicator
+    pub request: Option<usize>,
+    // Page that was last requested. Any other page received is of the playlist activated before
+    awaiting: usize,
+}
+
+// Keeps the queue going with related videos of last track when autoplay is on.
+// Painter asks for related videos once the last item of queue starts playing and communicator
+// appends the chosen ones to the queue. See `State::use_recommended`
+pub struct AutoplayState {
+    pub enabled: bool,
+    // Video id whose related videos should be fetched. Set by painter and taken by communicator
+    pub request: Option<String>,
+    // Video id whose related videos were last requested so that same track is not requested again
+    // in every refresh while it plays
+    requested_for: Option<String>,
+    // Ids of videos played or queued in this session. These are never added again by autoplay
+    history: HashSet<String>,
+}
+
+// Option under cursor while sleep timer picker is shown. See `utils::SLEEP_PICKER_TITLE`
+pub struct SleepPicker {
+    cursor: usize,
+    // Minutes typed while picker is shown. 0 if nothing is typed
+    typed_minutes: u64,
+}
+
 #[derive(Debug)]
 pub struct PlaybackBehaviour {
     // true if user wishes to suffle the playlist
     // false otherwise
     shuffle: bool,
-    // true if user wished to repeat all items from playlist. i.e when last music of playlist ends
-    // play the first music again
-    // flase if user wants to repeat the currently playing music over and over again
-    repeat: bool,
+    // Weather to repeat all items of queue, only the current one or nothing at all
+    repeat: queue::RepeatMode,
     // Current volume level. This is store here instead of fecthing with get_prop everytime
     volume: u8,
+    // Currently active loudness normalization mode. Initially set from config
+    loudness: config::LoudnessMode,
+    // Index of active preset in `AudioFilters::presets` of config. None if no preset is defined
+    audio_preset: Option<usize>,
+    // Current playback speed. 1.0 is normal. Same speed is kept for all tracks until changed
+    speed: f64,
 }
 
 pub struct State<'p> {
@@ -374,18 +646,86 @@ pub struct State<'p> {
     // second member is the string of searchbar when use pressed ENTER last time in searchbar
     pub search: (String, String),
 
+    // See documentation for respective struct
+    pub search_assist: SearchAssist,
+
     // Currently active window. In UI, this windows title is hilighted and keypress are evaluated
     // depending on active window
     pub active: Window,
 
-    // Currebntly fetched page for music/playlist/artist bar respectivery in index 0,1,2
-    // Initially it is whenever `filled_source` is changed. And is inceremented/decremented by 1 on
-    // next/prev respectively
-    pub fetched_page: [Option<usize>; 3],
+    // Pages of music/playlist/artist bar respectivery in index 0,1,2
+    // It is reset whenever `filled_source` is changed. And page is inceremented/decremented by 1
+    // on next/prev respectively. See documentation for respective struct
+    pub pages: [Pagination; 3],
 
-    // Main handler for mpv player. This isw backed my libmpv library
-    pub player: libmpv::Mpv,
+    // Generation of music/playlist/artist bar in index 0,1,2. Increased every time the list is
+    // asked to be filled and is sent along with the request. Response carrying older generation
+    // is of the request that was superseded so is dropped. See `communicator::Response::apply`
+    pub fill_generation: [u64; 3],
+
+    // Main handler for mpv player. This is either backed by libmpv library or by mpv process
+    // controlled over ipc socket depending on `MpvOptions::backend` in config.
+    pub player: Box<dyn utils::ExtendMpv + Send>,
 
     // See documentation for respective struct
     pub playback_behaviour: PlaybackBehaviour,
+
+    // Play order of the items in player queue. See documentation for respective struct
+    queue: queue::Queue,
+
+    // See documentation for respective struct
+    pub prefetch: PrefetchState,
+
+    // See documentation for respective struct
+    pub track_gain: TrackGainState,
+
+    // None if sleep timer is not set. See documentation for respective struct
+    pub sleep_timer: Option<SleepTimer>,
+
+    // See documentation for respective struct
+    pub sleep_picker: SleepPicker,
+
+    // See documentation for respective struct
+    pub autoplay: AutoplayState,
+
+    // See documentation for respective struct
+    pub chapters: ChapterState,
+
+    // See documentation for respective struct
+    pub lyrics: LyricsState,
+
+    // See documentation for respective struct
+    pub segments: SegmentSkipState,
+
+    // See documentation for respective struct
+    pub video: VideoState,
+
+    // Id of the channel to start artist radio from. Set by event handler and taken by
+    // communicator which collects the music and replaces the queue with it
+    pub artist_radio: Option<String>,
+
+    // See documentation for respective struct
+    pub trending: TrendingState,
+
+    // Sources listed in community section of sidebar. Starts from config and is saved back to
+    // config when something is added from ui
+    pub community: config::Community,
+
+    // Playlist whose pages are still being added to the queue. None once every page is added or
+    // queue is replaced with something else
+    pub playlist_feed: Option<PlaylistFeed>,
+
+    // Number of downloads still in progress. Application is not quit while some download is going
+    // on unless forced
+    pub downloads: u32,
+}
+
+// Everything painter is asked to handle. Painter owns the state and is the only one modifying it.
+// Event listener sends the user input, communicator sends the fetched response and ui is
+// repainted after handling each of those. See `draw_ui`
+pub enum Message {
+    Input(crossterm::event::Event),
+    // Nothing happened in last refresh rate. Repaint anyway so that status of player is synced
+    Tick,
+    Fetched(crate::communicator::Response),
 }
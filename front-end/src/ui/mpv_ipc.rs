@@ -0,0 +1,345 @@
+use crate::ui::utils::ExtendMpv;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Time to wait for single reply from mpv. If mpv does not reply within this time it is
+// considered as stuck and request is returned as failed instead of blocking the ui forever
+const REPLY_TIME_OUT: Duration = Duration::from_secs(5);
+
+// After spawning mpv it takes a while to create the socket. Try this many times to connect
+// waiting for CONNECT_RETRY_DELAY between each attempt
+const CONNECT_RETRY_COUNT: u32 = 100;
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+#[derive(Debug)]
+pub enum IpcError {
+    // Reading or writing to the socket failed. Includes the case where mpv has exited
+    Io(io::Error),
+    // mpv understood the request but replied with error. Inner string is the error reported by mpv
+    // eg: "property not found" or "property unavailable"
+    Mpv(String),
+    // Reply recived from mpv is not in expected json format
+    Protocol,
+}
+
+impl std::fmt::Display for IpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpcError::Io(err) => write!(f, "Cannot communicate with mpv. Error: {}", err),
+            IpcError::Mpv(err) => write!(f, "mpv replied with error: {}", err),
+            IpcError::Protocol => write!(f, "Unexpected reply from mpv"),
+        }
+    }
+}
+
+impl From<io::Error> for IpcError {
+    fn from(err: io::Error) -> Self {
+        IpcError::Io(err)
+    }
+}
+
+struct Connection {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+    // Every request is tagged with unique id so that reply to that request can be recognized
+    // among the event messages mpv sends to the same socket
+    last_request_id: u64,
+}
+
+// Player backend that drives a seperate mpv process through JSON IPC protocol.
+// See: https://mpv.io/manual/master/#json-ipc
+// mpv is started as `mpv --idle --input-ipc-server=<socket>` so that it keeps running without
+// anything to play and waits for commands in the socket
+pub struct MpvIpc {
+    connection: Mutex<Connection>,
+    // None when connected to already running mpv instead of spawning one
+    process: Option<Child>,
+    socket_path: PathBuf,
+}
+
+impl MpvIpc {
+    pub fn spawn(mpv_binary: &str, extra_args: &[String]) -> io::Result<Self> {
+        let socket_path =
+            std::env::temp_dir().join(format!("ytui_music-mpv-{}.sock", std::process::id()));
+        // A socket file may have been left by previous session which was not closed properly
+        std::fs::remove_file(&socket_path).ok();
+
+        let mut process = Command::new(mpv_binary)
+            .arg("--idle=yes")
+            .arg("--no-terminal")
+            .arg(format!(
+                "--input-ipc-server={}",
+                socket_path.to_string_lossy()
+            ))
+            .args(extra_args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let mut attempt = 0;
+        let mut ipc = loop {
+            match Self::connect(&socket_path) {
+                Ok(ipc) => break ipc,
+                Err(err) => {
+                    attempt += 1;
+                    // mpv have exited before creating socket. Eg: invalid option was passed
+                    if attempt >= CONNECT_RETRY_COUNT || process.try_wait()?.is_some() {
+                        process.kill().ok();
+                        return Err(err);
+                    }
+                    std::thread::sleep(CONNECT_RETRY_DELAY);
+                }
+            }
+        };
+
+        ipc.process = Some(process);
+        Ok(ipc)
+    }
+
+    // Connect to the mpv that is already listening in given socket
+    pub fn connect(socket_path: &Path) -> io::Result<Self> {
+        let stream = UnixStream::connect(socket_path)?;
+        Self::from_stream(stream, socket_path.to_path_buf())
+    }
+
+    fn from_stream(stream: UnixStream, socket_path: PathBuf) -> io::Result<Self> {
+        stream.set_read_timeout(Some(REPLY_TIME_OUT))?;
+        stream.set_write_timeout(Some(REPLY_TIME_OUT))?;
+        let writer = stream.try_clone()?;
+
+        Ok(MpvIpc {
+            connection: Mutex::new(Connection {
+                reader: BufReader::new(stream),
+                writer,
+                last_request_id: 0,
+            }),
+            process: None,
+            socket_path,
+        })
+    }
+
+    // Send a command and wait for it's reply. Return the `data` field of reply which is
+    // Value::Null for commands that do not return anything
+    pub fn request(&self, command: &[Value]) -> Result<Value, IpcError> {
+        let mut connection = self.connection.lock().unwrap();
+        connection.last_request_id += 1;
+        let request_id = connection.last_request_id;
+
+        let mut message = json!({ "command": command, "request_id": request_id }).to_string();
+        message.push('\n');
+        connection.writer.write_all(message.as_bytes())?;
+
+        // mpv may send any number of event messages (and reply of previous timed-out request)
+        // before the reply of this request. Skip all of those
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if connection.reader.read_line(&mut line)? == 0 {
+                return Err(IpcError::Io(io::ErrorKind::UnexpectedEof.into()));
+            }
+
+            let reply: Value = serde_json::from_str(&line).map_err(|_| IpcError::Protocol)?;
+            if reply.get("request_id").and_then(Value::as_u64) != Some(request_id) {
+                continue;
+            }
+
+            return match reply.get("error").and_then(Value::as_str) {
+                Some("success") => Ok(reply.get("data").cloned().unwrap_or(Value::Null)),
+                Some(err) => Err(IpcError::Mpv(err.to_string())),
+                None => Err(IpcError::Protocol),
+            };
+        }
+    }
+}
+
+impl ExtendMpv for MpvIpc {
+    fn set_prop(&self, name: &str, value: &str) -> bool {
+        // `set` takes the value as string same as in mpv.conf or in command line option
+        // so no need to care about type of property
+        self.request(&[json!("set"), json!(name), json!(value)])
+            .is_ok()
+    }
+
+    fn get_prop(&self, name: &str) -> Option<String> {
+        match self.request(&[json!("get_property_string"), json!(name)]) {
+            Ok(Value::String(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn run_command(&self, name: &str, args: &[&str]) -> bool {
+        let mut command = Vec::with_capacity(args.len() + 1);
+        command.push(json!(name));
+        command.extend(args.iter().map(|arg| json!(arg)));
+
+        self.request(&command).is_ok()
+    }
+}
+
+impl Drop for MpvIpc {
+    fn drop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            // Ask mpv to quit nicely and kill if it is still running anyway
+            self.request(&[json!("quit")]).ok();
+            process.kill().ok();
+            process.wait().ok();
+            std::fs::remove_file(&self.socket_path).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::thread::JoinHandle;
+
+    // Start a fake mpv listening in socket. For each expected command in `script`, server checks
+    // that recived command is same and reply with given reply. Before replying, an event message is
+    // always sent to make sure client skips them.
+    // reply is json value of form { "error": .., "data": .. } and request_id is filled by server
+    fn fake_mpv(name: &str, script: Vec<(Value, Value)>) -> (PathBuf, JoinHandle<()>) {
+        let socket_path = std::env::temp_dir().join(format!(
+            "ytui_music-test-{}-{}.sock",
+            name,
+            std::process::id()
+        ));
+        std::fs::remove_file(&socket_path).ok();
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            for (expected_command, mut reply) in script {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let request: Value = serde_json::from_str(&line).unwrap();
+                assert_eq!(request["command"], expected_command);
+
+                reply["request_id"] = request["request_id"].clone();
+                let event = json!({ "event": "property-change", "name": "volume" });
+                writeln!(writer, "{}", event).unwrap();
+                writeln!(writer, "{}", reply).unwrap();
+            }
+        });
+
+        (socket_path, server)
+    }
+
+    #[test]
+    fn set_and_get_property() {
+        let (socket_path, server) = fake_mpv(
+            "set-get",
+            vec![
+                (
+                    json!(["set", "loop-playlist", "inf"]),
+                    json!({ "error": "success" }),
+                ),
+                (
+                    json!(["get_property_string", "media-title"]),
+                    json!({ "error": "success", "data": "Some music" }),
+                ),
+                (
+                    json!(["get_property_string", "duration"]),
+                    json!({ "error": "property unavailable" }),
+                ),
+            ],
+        );
+        let mpv = MpvIpc::connect(&socket_path).unwrap();
+
+        mpv.repeat_playlist();
+        assert_eq!(mpv.get_prop("media-title"), Some("Some music".to_string()));
+        assert_eq!(mpv.get_prop_f64("duration"), None);
+
+        server.join().unwrap();
+        std::fs::remove_file(&socket_path).ok();
+    }
+
+    #[test]
+    fn change_volume_is_clamped() {
+        let (socket_path, server) = fake_mpv(
+            "volume",
+            vec![
+                (
+                    json!(["get_property_string", "volume"]),
+                    json!({ "error": "success", "data": "95.000000" }),
+                ),
+                (
+                    json!(["set", "volume", "100"]),
+                    json!({ "error": "success" }),
+                ),
+                (
+                    json!(["get_property_string", "volume"]),
+                    json!({ "error": "success", "data": "100.000000" }),
+                ),
+                (
+                    json!(["set", "volume", "90"]),
+                    json!({ "error": "error running command" }),
+                ),
+            ],
+        );
+        let mpv = MpvIpc::connect(&socket_path).unwrap();
+
+        assert_eq!(mpv.change_volume(10), Some(100));
+        assert_eq!(mpv.change_volume(-10), None);
+
+        server.join().unwrap();
+        std::fs::remove_file(&socket_path).ok();
+    }
+
+    #[test]
+    fn load_and_navigate() {
+        let url = "https://www.youtube.com/watch?v=xyz";
+        let (socket_path, server) = fake_mpv(
+            "load",
+            vec![
+                (
+                    json!(["loadfile", url, "append"]),
+                    json!({ "error": "success" }),
+                ),
+                (
+                    json!(["playlist-next", "weak"]),
+                    json!({ "error": "success" }),
+                ),
+                (
+                    json!(["set", "pause", "yes"]),
+                    json!({ "error": "success" }),
+                ),
+                (
+                    json!(["seek", "-10", "relative"]),
+                    json!({ "error": "success" }),
+                ),
+            ],
+        );
+        let mpv = MpvIpc::connect(&socket_path).unwrap();
+
+        assert!(mpv.load_url(url, true));
+        mpv.play_next();
+        assert!(mpv.set_pause(true));
+        assert!(mpv.seek(-10.0));
+
+        server.join().unwrap();
+        std::fs::remove_file(&socket_path).ok();
+    }
+
+    #[test]
+    fn fails_when_mpv_disconnects() {
+        let (socket_path, server) = fake_mpv("disconnect", Vec::new());
+        let mpv = MpvIpc::connect(&socket_path).unwrap();
+        server.join().unwrap();
+
+        assert!(matches!(
+            mpv.request(&[json!("get_property_string"), json!("volume")]),
+            Err(IpcError::Io(_))
+        ));
+        std::fs::remove_file(&socket_path).ok();
+    }
+}
@@ -0,0 +1,280 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatMode {
+    // Stop after last item of queue
+    Off,
+    // Play the current item over and over again
+    One,
+    // Play the queue again from start after last item
+    All,
+}
+
+impl RepeatMode {
+    // Mode to switch to when cycling with shortcut key
+    pub fn next(self) -> Self {
+        match self {
+            RepeatMode::All => RepeatMode::One,
+            RepeatMode::One => RepeatMode::Off,
+            RepeatMode::Off => RepeatMode::All,
+        }
+    }
+}
+
+// (from, to): Move the item at position `from` so that it takes the place of item at `to`.
+// `from` is never less than `to` so this is exactly what mpv `playlist-move from to` does
+pub type Move = (usize, usize);
+
+// Play order of the player queue. Items are referred by their index in the original order i.e the
+// order in which they were added to the player queue.
+// Player keeps playing the queue from top to bottom as usual. Instead of asking player to shuffle,
+// the items are moved in player queue to the order decided here. That way original order can
+// always be restored and every item is played once before any item is played again.
+#[derive(Debug)]
+pub struct Queue {
+    // order[i] is the original index of item at position i of player queue
+    order: Vec<usize>,
+    // Position in player queue that is currently playing
+    position: usize,
+    shuffled: bool,
+}
+
+impl Queue {
+    pub fn new(len: usize, position: usize) -> Self {
+        Queue {
+            order: (0..len).collect(),
+            position: position.min(len.saturating_sub(1)),
+            shuffled: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: usize) {
+        self.position = position.min(self.len().saturating_sub(1));
+    }
+
+    // Currently playing item is moved to top and is followed by rest of the items in random order
+    // So every other item is played once before the queue is exhausted
+    pub fn shuffle<R: Rng>(&mut self, rng: &mut R) -> Vec<Move> {
+        self.shuffled = true;
+        let current = match self.order.get(self.position) {
+            Some(&current) => current,
+            None => return Vec::new(),
+        };
+
+        let mut new_order = self.others_than(current);
+        new_order.shuffle(rng);
+        new_order.insert(0, current);
+
+        self.position = 0;
+        self.rearrange(&new_order)
+    }
+
+    // Bring back the original order. Currently playing item keeps playing
+    pub fn unshuffle(&mut self) -> Vec<Move> {
+        self.shuffled = false;
+        let current = match self.order.get(self.position) {
+            Some(&current) => current,
+            None => return Vec::new(),
+        };
+
+        let original_order = (0..self.len()).collect::<Vec<_>>();
+        self.position = current;
+        self.rearrange(&original_order)
+    }
+
+    // `count` items were appended to the end of player queue. When shuffled, those are spread at
+    // random among the items yet to be played in this round and rest of the order is kept as it
+    // is. So items already played are not played again because the queue grew
+    pub fn extend<R: Rng>(&mut self, count: usize, rng: &mut R) -> Vec<Move> {
+        let old_len = self.len();
+        self.order.extend(old_len..old_len + count);
+        if !self.shuffled || old_len == 0 {
+            return Vec::new();
+        }
+
+        let mut new_order = self.order[..old_len].to_vec();
+        for item in old_len..old_len + count {
+            let at = rng.gen_range(self.position + 1..=new_order.len());
+            new_order.insert(at, item);
+        }
+        self.rearrange(&new_order)
+    }
+
+    // When last item of shuffled queue is playing and queue is repeated, arrange the rest of the
+    // items in new random order so that next round is also in different order and still plays
+    // every item once. Playing item stays at the end so that it is played last in next round
+    // which makes sure same item is never played twice in a row.
+    // Should only be called once when last item starts to play
+    pub fn prepare_next_round<R: Rng>(&mut self, rng: &mut R) -> Vec<Move> {
+        if !self.shuffled || self.position + 1 != self.len() {
+            return Vec::new();
+        }
+
+        let current = self.order[self.position];
+        let mut new_order = self.others_than(current);
+        new_order.shuffle(rng);
+        new_order.push(current);
+
+        self.rearrange(&new_order)
+    }
+
+    fn others_than(&self, item: usize) -> Vec<usize> {
+        self.order
+            .iter()
+            .copied()
+            .filter(|&other| other != item)
+            .collect()
+    }
+
+    // Change the order to new_order and return the moves that should be applied to player queue
+    // to get the same order. Position is not touched here
+    fn rearrange(&mut self, new_order: &[usize]) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for (target, item) in new_order.iter().enumerate() {
+            // Everything before target is already in place so item is always found at or after it
+            let from = target
+                + self.order[target..]
+                    .iter()
+                    .position(|other| other == item)
+                    .unwrap();
+            if from != target {
+                let moved = self.order.remove(from);
+                self.order.insert(target, moved);
+                moves.push((from, target));
+            }
+        }
+
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // Apply the moves as mpv would do to the player queue
+    fn apply_moves(player_queue: &mut Vec<usize>, moves: &[Move]) {
+        for &(from, to) in moves {
+            assert!(from >= to);
+            let moved = player_queue.remove(from);
+            player_queue.insert(to, moved);
+        }
+    }
+
+    #[test]
+    fn shuffle_and_restore() {
+        let mut rng = StdRng::seed_from_u64(533);
+        let mut player_queue = (0..10).collect::<Vec<_>>();
+        let mut queue = Queue::new(10, 4);
+
+        let moves = queue.shuffle(&mut rng);
+        apply_moves(&mut player_queue, &moves);
+        assert_eq!(player_queue, queue.order);
+        assert_eq!(queue.position(), 0);
+        assert_eq!(player_queue[0], 4);
+        let mut sorted = player_queue.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+
+        queue.set_position(6);
+        let playing = player_queue[6];
+        let moves = queue.unshuffle();
+        apply_moves(&mut player_queue, &moves);
+        assert_eq!(player_queue, (0..10).collect::<Vec<_>>());
+        assert_eq!(queue.position(), playing);
+    }
+
+    #[test]
+    fn no_repeat_until_exhausted() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let len = 7;
+        let mut player_queue = (0..len).collect::<Vec<_>>();
+        let mut queue = Queue::new(len, 2);
+        apply_moves(&mut player_queue, &queue.shuffle(&mut rng));
+
+        // Play three rounds the way player does with repeat all
+        let mut played = Vec::new();
+        for _ in 0..3 {
+            for position in 0..len {
+                queue.set_position(position);
+                played.push(player_queue[position]);
+                apply_moves(&mut player_queue, &queue.prepare_next_round(&mut rng));
+                assert_eq!(player_queue, queue.order);
+            }
+        }
+
+        for round in played.chunks(len) {
+            let mut sorted = round.to_vec();
+            sorted.sort_unstable();
+            assert_eq!(sorted, (0..len).collect::<Vec<_>>());
+        }
+        assert!(played.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn extend_shuffles_only_unplayed() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut player_queue = (0..6).collect::<Vec<_>>();
+        let mut queue = Queue::new(6, 0);
+        apply_moves(&mut player_queue, &queue.shuffle(&mut rng));
+        queue.set_position(3);
+        let played = player_queue[..=3].to_vec();
+        let unplayed = player_queue[4..].to_vec();
+
+        player_queue.extend(6..10);
+        apply_moves(&mut player_queue, &queue.extend(4, &mut rng));
+        assert_eq!(player_queue, queue.order);
+        assert_eq!(queue.position(), 3);
+        assert_eq!(&player_queue[..=3], played.as_slice());
+        // Items yet to be played keep their order and new ones are somewhere among them
+        let rest = &player_queue[4..];
+        assert_eq!(
+            rest.iter()
+                .filter(|item| **item < 6)
+                .copied()
+                .collect::<Vec<_>>(),
+            unplayed
+        );
+        let mut sorted = rest.to_vec();
+        sorted.sort_unstable();
+        let mut expected = unplayed.iter().copied().chain(6..10).collect::<Vec<_>>();
+        expected.sort_unstable();
+        assert_eq!(sorted, expected);
+
+        // Unshuffled queue is only extended
+        let mut queue = Queue::new(3, 1);
+        assert!(queue.extend(2, &mut rng).is_empty());
+        assert_eq!(queue.order, (0..5).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unshuffled_queue_is_left_as_it_is() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut queue = Queue::new(5, 4);
+        assert!(queue.prepare_next_round(&mut rng).is_empty());
+        assert!(queue.unshuffle().is_empty());
+
+        let mut empty = Queue::new(0, 0);
+        assert!(empty.shuffle(&mut rng).is_empty());
+        assert!(empty.prepare_next_round(&mut rng).is_empty());
+    }
+
+    #[test]
+    fn repeat_mode_cycles() {
+        let mut mode = RepeatMode::All;
+        for expected in [RepeatMode::One, RepeatMode::Off, RepeatMode::All] {
+            mode = mode.next();
+            assert_eq!(mode, expected);
+        }
+    }
+}
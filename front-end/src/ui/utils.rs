@@ -1,13 +1,13 @@
 use crate::ui;
 use fetcher::ExtendDuration;
+use rand::seq::SliceRandom;
 use std::borrow::Cow;
 use tui::{self, text::Line};
 use ui::shared_import::*;
 
-pub const SIDEBAR_LIST_COUNT: usize = 6;
+pub const SIDEBAR_LIST_COUNT: usize = 5;
 pub const SIDEBAR_LIST_ITEMS: [&str; SIDEBAR_LIST_COUNT] = [
     "Trending",
-    "Youtube Community",
     "Liked songs",
     "My playlist",
     "Following",
@@ -15,14 +15,60 @@ pub const SIDEBAR_LIST_ITEMS: [&str; SIDEBAR_LIST_COUNT] = [
 ];
 use config::initilize::{
     CONFIG, STORAGE, TB_FAVOURATES_ARTIST, TB_FAVOURATES_MUSIC, TB_FAVOURATES_PLAYLIST,
+    TB_SEARCH_HISTORY,
 };
 
+// Label of audio filter added for loudness normalization. See `ExtendMpv::set_audio_filter`
+const LOUDNESS_FILTER_LABEL: &str = "ytui-loudness";
+const LOUDNORM_FILTER: &str = "lavfi=[loudnorm=I=-16:TP=-1.5:LRA=11]";
+const DYNAUDNORM_FILTER: &str = "lavfi=[dynaudnorm]";
+// Label of audio filter added for equalizer preset. Kept seperate from loudness filter so that
+// both can be used together
+const EQUALIZER_FILTER_LABEL: &str = "ytui-equalizer";
+// Label of scaletempo2 filter which keeps the pitch same when speed is changed
+const TEMPO_FILTER_LABEL: &str = "ytui-tempo";
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 4.0;
+// Number of related videos added to queue at once by autoplay
+const AUTOPLAY_BATCH: usize = 5;
+// Related videos with duration close to this are prefered by autoplay
+const TYPICAL_SONG_SECS: f64 = 210.0;
+// Options of sleep timer in minutes. Picker also has end of track, end of queue and off along
+// with these. Any other minutes can be typed in picker
+const SLEEP_TIMER_MINUTES: [u64; 5] = [15, 30, 45, 60, 90];
+// Title of popup showing the options of sleep timer. Up/Down moves the cursor there, digits type
+// the minutes and Enter sets the timer
+pub const SLEEP_PICKER_TITLE: &str = "Sleep timer";
+// Typing more than this in sleep timer picker starts again from the last digit
+const MAX_SLEEP_MINUTES: u64 = 24 * 60;
+// Gauge of live stream fills up in these many seconds and starts again
+const LIVE_GAUGE_SECS: u64 = 30;
+// Title of popup showing the regions of trending. Up/Down moves the cursor there and Enter picks
+// the region under it
+pub const REGION_PICKER_TITLE: &str = "Trending region";
+// Number of previous queries kept in local database
+const MAX_SEARCH_HISTORY: usize = 100;
+// At most these many previous queries are put before the suggestions of server
+const HISTORY_COMPLETIONS: usize = 3;
+// Number of completions shown below search box
+const MAX_COMPLETIONS: usize = 8;
+// Suggestions of typed text are fetched once it stays same for this long
+const SUGGESTIONS_DELAY: Duration = Duration::from_millis(300);
+// In track gain mode, quiet tracks are boosted at most by this much to avoid clipping
+const MAX_TRACK_GAIN_DB: f64 = 6.0;
+// Next page of list is fetched when cursor gets within these many rows of the end of list
+const PRELOAD_ROWS: usize = 3;
+// At most these many pages are kept in a list. Pages far from the cursor are dropped when more
+// are loaded and are fetched again if cursor goes back there. So that huge playlist do not stay in
+// memory as a whole
+const MAX_LOADED_PAGES: usize = 20;
+
 pub fn show_pupop_text<'a, B>(frame: &mut tui::terminal::Frame<B>, text: [&'a str; 2], area: &Rect)
 where
     B: Backend,
 {
     let block = Block::active(text[0].to_string());
-    let text = Span::raw(text[1]);
+    let text = Text::from(text[1]);
     let paragraph = Paragraph::new(text)
         .alignment(Alignment::Center)
         .wrap(widgets::Wrap { trim: true })
@@ -91,6 +137,28 @@ impl<'parent> ui::TopLayout {
         ]);
         Paragraph::new(text).block(block)
     }
+
+    // Completions of search box shown below it. Those from history are shown in different color
+    // than the suggestions of server
+    pub fn get_search_completions(state: &'parent ui::State) -> List<'parent> {
+        let history = &state.search_assist.history;
+        List::new(
+            state
+                .search_completions()
+                .into_iter()
+                .map(|completion| {
+                    let color = if history.iter().any(|previous| previous == completion) {
+                        CONFIG.theme.color_secondary
+                    } else {
+                        CONFIG.theme.color_primary
+                    };
+                    ListItem::new(Span::styled(completion, Style::list_idle().fg(rgb!(color))))
+                })
+                .collect::<Vec<ListItem>>(),
+        )
+        .highlight_style(Style::list_highlight())
+        .block(Block::active("Suggestions ".to_owned()))
+    }
 }
 
 impl<'parent> ui::MainLayout {
@@ -120,23 +188,46 @@ impl<'parent> ui::MiddleLayout {
         }
     }
 
-    pub fn get_music_container(state: &'parent mut ui::State) -> Table<'parent> {
-        let block = match state.active {
-            ui::Window::Musicbar => Block::active("Music ".to_owned()),
+    // Table is returned along with the state of that table so that both can be borrowed from
+    // the state at once while rendering
+    pub fn get_music_container(
+        active: &ui::Window,
+        source: &ui::MusicbarSource,
+        pagination: &ui::Pagination,
+        musicbar: &'parent mut (Vec<fetcher::MusicUnit>, TableState),
+    ) -> (Table<'parent>, &'parent mut TableState) {
+        let (data_list, table_state) = musicbar;
+        let name = match source {
+            ui::MusicbarSource::Trending(region, category) => {
+                Cow::Owned(format!("Trending {} in {}", category.name(), region))
+            }
+            _ => Cow::Borrowed("Music"),
+        };
+        let block = match active {
+            ui::Window::Musicbar => {
+                Block::active(list_title(&name, pagination, table_state.selected()))
+            }
             _ => {
-                state.musicbar.1.select(None);
-                Block::with_title("Music ".to_owned())
+                table_state.select(None);
+                Block::with_title(list_title(&name, pagination, None))
             }
         };
 
-        let data_list = &state.musicbar.0;
         let items: Vec<Row> = data_list
             .iter()
             .map(|music| {
+                let length = if music.live {
+                    Cell::from(Span::styled(
+                        "LIVE",
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    ))
+                } else {
+                    Cell::from(music.duration.to_string())
+                };
                 Row::new(vec![
-                    music.name.as_str(),
-                    music.artist.as_str(),
-                    music.duration.as_str(),
+                    Cell::from(music.name.as_str()),
+                    Cell::from(music.artist.as_str()),
+                    length,
                 ])
             })
             .collect();
@@ -155,7 +246,7 @@ impl<'parent> ui::MiddleLayout {
             .highlight_style(Style::list_highlight())
             .block(block);
 
-        table
+        (table, table_state)
     }
 }
 
@@ -171,22 +262,28 @@ impl<'parent> ui::MiddleBottom {
         }
     }
 
-    pub fn get_playlist_container(state: &'parent mut ui::State) -> Table<'parent> {
-        let block = match state.active {
-            ui::Window::Playlistbar => Block::active("Playlist ".to_owned()),
+    pub fn get_playlist_container(
+        active: &ui::Window,
+        pagination: &ui::Pagination,
+        playlistbar: &'parent mut (Vec<fetcher::PlaylistUnit>, TableState),
+    ) -> (Table<'parent>, &'parent mut TableState) {
+        let (data_list, table_state) = playlistbar;
+        let block = match active {
+            ui::Window::Playlistbar => {
+                Block::active(list_title("Playlist", pagination, table_state.selected()))
+            }
             _ => {
-                state.playlistbar.1.select(None);
-                Block::with_title("Playlist ".to_owned())
+                table_state.select(None);
+                Block::with_title(list_title("Playlist", pagination, None))
             }
         };
-        let data_list = &state.playlistbar.0;
         let items: Vec<Row> = data_list
             .iter()
             .map(|playlist| {
                 Row::new(vec![
-                    playlist.video_count.as_str(),
-                    playlist.name.as_str(),
-                    playlist.author.as_str(),
+                    Cell::from(count_text(playlist.video_count)),
+                    Cell::from(playlist.name.as_str()),
+                    Cell::from(playlist.author.as_str()),
                 ])
             })
             .collect();
@@ -205,22 +302,30 @@ impl<'parent> ui::MiddleBottom {
             .highlight_style(Style::list_highlight())
             .block(block);
 
-        table
+        (table, table_state)
     }
 
-    pub fn get_artist_container(state: &'parent mut ui::State) -> Table<'parent> {
+    pub fn get_artist_container(
+        active: &ui::Window,
+        pagination: &ui::Pagination,
+        artistbar: &'parent mut (Vec<fetcher::ArtistUnit>, TableState),
+    ) -> (Table<'parent>, &'parent mut TableState) {
+        let (data_list, table_state) = artistbar;
         let block;
-        if state.active == ui::Window::Artistbar {
-            block = Block::active("Artist ".to_string());
+        if *active == ui::Window::Artistbar {
+            block = Block::active(list_title("Artist", pagination, table_state.selected()));
         } else {
-            block = Block::with_title("Artist ".to_string());
-            state.artistbar.1.select(None);
+            block = Block::with_title(list_title("Artist", pagination, None));
+            table_state.select(None);
         }
-        let data_list = &state.artistbar;
         let items: Vec<Row> = data_list
-            .0
             .iter()
-            .map(|artist| Row::new(vec![artist.video_count.as_str(), artist.name.as_str()]))
+            .map(|artist| {
+                Row::new(vec![
+                    Cell::from(count_text(artist.video_count)),
+                    Cell::from(artist.name.as_str()),
+                ])
+            })
             .collect();
         let table = Table::new(items)
             .header(Row::new(vec!["#", "Name"]).style(Style::list_title()))
@@ -230,7 +335,48 @@ impl<'parent> ui::MiddleBottom {
             .highlight_style(Style::list_highlight())
             .block(block);
 
-        table
+        (table, table_state)
+    }
+
+    // Lyrics are shown over the playlist and artist pane. Line being sung is highlighted and
+    // kept in the middle of pane as the music plays
+    pub fn get_lyrics_pane(state: &'parent ui::State, height: u16) -> Paragraph<'parent> {
+        let block = Block::active("Lyrics ".to_string());
+        let lyrics = &state.lyrics;
+        if lyrics.lines.is_empty() {
+            let message = if lyrics.fetching {
+                "Fetching lyrics.."
+            } else {
+                "No lyrics found.."
+            };
+            return Paragraph::new(message)
+                .style(Style::list_idle())
+                .alignment(Alignment::Center)
+                .block(block);
+        }
+
+        let current = state.current_lyric();
+        let lines = lyrics
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let style = if Some(index) == current {
+                    Style::list_highlight()
+                } else {
+                    Style::list_idle()
+                };
+                Line::from(Span::styled(line.text.as_str(), style))
+            })
+            .collect::<Vec<_>>();
+
+        // 2 rows are taken by border
+        let middle = height.saturating_sub(2) / 2;
+        let scroll = (current.unwrap_or_default() as u16).saturating_sub(middle);
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .scroll((scroll, 0))
+            .block(block)
     }
 }
 
@@ -241,21 +387,30 @@ impl<'parent> ui::SideBar {
         // | <playing | paused>
         // | R-1
         // | S-1
+        // | autoplay
+        // | video
+        // | <normalize mode>
+        // | eq: <preset>
         // ----------------
-        // Total height: 6
-        let status_height: u16 = 6;
-        let list_height = parent.height.checked_sub(status_height).unwrap_or_default();
+        // Total height: 10
+        let status_height: u16 = 10;
+        // All the shortcuts along with the border. Rest is for community groups
+        let shortcut_height = SIDEBAR_LIST_COUNT as u16 + 2;
+        let community_height = parent
+            .height
+            .saturating_sub(status_height + shortcut_height);
 
         let layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(list_height),
+                Constraint::Length(shortcut_height),
+                Constraint::Length(community_height),
                 Constraint::Length(status_height),
             ])
             .split(parent);
 
         ui::SideBar {
-            layout: [layout[0], layout[1]],
+            layout: [layout[0], layout[1], layout[2]],
         }
     }
 
@@ -278,6 +433,28 @@ impl<'parent> ui::SideBar {
         .highlight_style(Style::list_highlight())
         .block(block)
     }
+
+    pub fn get_community(state: &'parent ui::State) -> List<'parent> {
+        let block = match state.active {
+            ui::Window::Sidebar => Block::active("Community ".to_owned()),
+            _ => Block::with_title("Community ".to_owned()),
+        };
+        List::new(
+            state
+                .community
+                .groups()
+                .into_iter()
+                .map(|group| {
+                    ListItem::new(Span::styled(
+                        group,
+                        Style::list_idle().fg(rgb!(CONFIG.theme.color_primary)),
+                    ))
+                })
+                .collect::<Vec<ListItem>>(),
+        )
+        .highlight_style(Style::list_highlight())
+        .block(block)
+    }
 }
 
 impl<'parent> ui::BottomLayout {
@@ -291,18 +468,38 @@ impl<'parent> ui::BottomLayout {
     }
 
     pub fn get_status_bar(state: &'parent ui::State) -> Gauge<'parent> {
-        let content;
+        let content: Cow<'parent, str>;
         if let Some((name, _)) = &state.bottom.playing {
-            content = name.as_str()
+            // For long videos with chapters, also show which chapter is playing
+            content = match state.current_chapter() {
+                Some(current) => {
+                    Cow::Owned(format!("{} | {}", state.chapters.list[current].title, name))
+                }
+                None => Cow::Borrowed(name.as_str()),
+            };
         } else {
-            content = ">> Play some Music <<"
+            content = Cow::Borrowed(">> Play some Music <<")
         };
 
-        let heading = format!(
-            "{} / {}",
-            state.bottom.music_elapse.to_string(),
-            state.bottom.music_duration.to_string()
-        );
+        let mut heading = if state.bottom.live {
+            format!("{} / LIVE", state.bottom.music_elapse.to_string())
+        } else {
+            format!(
+                "{} / {}",
+                state.bottom.music_elapse.to_string(),
+                state.bottom.music_duration.to_string()
+            )
+        };
+        if let Some(timer) = &state.sleep_timer {
+            let remaining = match timer.at {
+                ui::SleepAt::Time(at, _) => {
+                    at.saturating_duration_since(Instant::now()).to_string()
+                }
+                ui::SleepAt::EndOfTrack(_) => "end of track".to_string(),
+                ui::SleepAt::EndOfQueue(_) => "end of queue".to_string(),
+            };
+            heading.push_str(&format!(" | Sleep: {}", remaining));
+        }
 
         let mut block;
         if state.active == ui::Window::BottomControl {
@@ -312,8 +509,12 @@ impl<'parent> ui::BottomLayout {
         }
         block = block.title_alignment(Alignment::Center);
 
-        let mut ratio =
-            state.bottom.music_elapse.as_secs_f64() / state.bottom.music_duration.as_secs_f64();
+        let mut ratio = if state.bottom.live {
+            // Live stream never ends. Keep the bar moving to show that it is still playing
+            (state.bottom.music_elapse.as_secs() % LIVE_GAUGE_SECS) as f64 / LIVE_GAUGE_SECS as f64
+        } else {
+            state.bottom.music_elapse.as_secs_f64() / state.bottom.music_duration.as_secs_f64()
+        };
         if ratio > 1.0 {
             ratio = 1.0
         } else if ratio.is_nan() || ratio < 0.0 {
@@ -348,8 +549,13 @@ impl<'parent> ui::BottomLayout {
         }
 
         let mut repeat = Span::styled("repeat-all", Style::list_highlight());
-        if !state.playback_behaviour.repeat {
-            repeat.content = Cow::Borrowed("repeat-one");
+        match state.playback_behaviour.repeat {
+            ui::queue::RepeatMode::All => {}
+            ui::queue::RepeatMode::One => repeat.content = Cow::Borrowed("repeat-one"),
+            ui::queue::RepeatMode::Off => {
+                repeat.content = Cow::Borrowed("repeat");
+                repeat.style = repeat.style.add_modifier(Modifier::CROSSED_OUT);
+            }
         }
 
         let mut suffle = Span::styled("suffle", Style::list_highlight());
@@ -358,13 +564,49 @@ impl<'parent> ui::BottomLayout {
         }
 
         let volume = Span::styled(
-            format!("Vol: {}", state.playback_behaviour.volume),
+            format!(
+                "Vol: {} {:.2}x",
+                state.playback_behaviour.volume, state.playback_behaviour.speed
+            ),
             Style::list_highlight(),
         );
+
+        let mut loudness = Span::styled("normalize", Style::list_highlight());
+        match state.playback_behaviour.loudness {
+            config::LoudnessMode::Off => {
+                loudness.style = loudness.style.add_modifier(Modifier::CROSSED_OUT);
+            }
+            config::LoudnessMode::Loudnorm => loudness.content = Cow::Borrowed("loudnorm"),
+            config::LoudnessMode::Dynaudnorm => loudness.content = Cow::Borrowed("dynaudnorm"),
+            config::LoudnessMode::TrackGain => loudness.content = Cow::Borrowed("track-gain"),
+        }
+
+        let preset_name = state
+            .playback_behaviour
+            .audio_preset
+            .and_then(|index| CONFIG.audio_filters.presets.get(index))
+            .map(|preset| preset.name.as_str())
+            .unwrap_or("none");
+        let audio_preset = Span::styled(format!("eq: {}", preset_name), Style::list_highlight());
+
+        let mut autoplay = Span::styled("autoplay", Style::list_highlight());
+        if !state.autoplay.enabled {
+            autoplay.style = autoplay.style.add_modifier(Modifier::CROSSED_OUT);
+        }
+
+        let mut video = Span::styled("video", Style::list_highlight());
+        if !state.video.enabled {
+            video.style = video.style.add_modifier(Modifier::CROSSED_OUT);
+        }
+
         let content = Text::from(vec![
             Line::from(volume),
             Line::from(repeat),
             Line::from(suffle),
+            Line::from(autoplay),
+            Line::from(video),
+            Line::from(loudness),
+            Line::from(audio_preset),
             Line::from(paused_status),
         ]);
 
@@ -489,11 +731,12 @@ impl ui::Position {
             search: top_section.layout[0],
             status: top_section.layout[1],
             shortcut: sidebar.layout[0],
+            community: sidebar.layout[1],
             music: middle_section.layout,
             playlist: middle_bottom.layout[0],
             artist: middle_bottom.layout[1],
             music_info: bottom_section.layout,
-            bottom_icons: sidebar.layout[1],
+            bottom_icons: sidebar.layout[2],
             popup: popup_pos,
         }
     }
@@ -501,8 +744,9 @@ impl ui::Position {
 
 impl Default for ui::State<'_> {
     fn default() -> Self {
-        let mpv = libmpv::Mpv::new().unwrap();
+        let mpv = spawn_player();
         mpv.configure_defult();
+        mpv.enable_gapless();
         mpv.cache_for(10);
         // By default repeat the playlist. Set playlist to repeat
         mpv.repeat_playlist();
@@ -512,15 +756,24 @@ impl Default for ui::State<'_> {
 
         let mut sidebar_list_state = ListState::default();
         sidebar_list_state.select(Some(0));
-        ui::State {
+        let mut state = ui::State {
             status: "@sudipghimire533",
             sidebar: sidebar_list_state,
             musicbar: (Vec::new(), TableState::default()),
             playlistbar: (Vec::new(), TableState::default()),
             artistbar: (Vec::new(), TableState::default()),
             search: (String::new(), String::new()),
+            search_assist: ui::SearchAssist {
+                history: read_search_history(),
+                suggestions: Vec::new(),
+                typed: String::new(),
+                history_pos: None,
+                completion_pos: None,
+                edited_at: None,
+            },
             active: ui::Window::Sidebar,
-            fetched_page: [None; 3],
+            pages: [ui::Pagination::default(); 3],
+            fill_generation: [0; 3],
             filled_source: (
                 ui::MusicbarSource::RecentlyPlayed,
                 ui::PlaylistbarSource::RecentlyPlayed,
@@ -530,63 +783,137 @@ impl Default for ui::State<'_> {
                 playing: None,
                 music_duration: Duration::new(0, 0),
                 music_elapse: Duration::new(0, 0),
+                live: false,
+                known_live: HashSet::new(),
             },
             player: mpv,
             playback_behaviour: ui::PlaybackBehaviour {
                 shuffle: false,
-                repeat: true,
+                repeat: ui::queue::RepeatMode::All,
                 volume: 100,
+                loudness: config::LoudnessMode::Off,
+                audio_preset: None,
+                speed: 1.0,
+            },
+            queue: ui::queue::Queue::new(0, 0),
+            prefetch: ui::PrefetchState {
+                playlist_pos: None,
+                request: ui::Prefetch::Idle,
+                resolved: HashMap::new(),
+            },
+            track_gain: ui::TrackGainState {
+                applied_for: None,
+                request: None,
+                requested_for: None,
+                known: HashMap::new(),
             },
+            sleep_timer: None,
+            sleep_picker: ui::SleepPicker {
+                cursor: 0,
+                typed_minutes: 0,
+            },
+            chapters: ui::ChapterState {
+                path: None,
+                list: Vec::new(),
+                request: None,
+                requested: false,
+            },
+            lyrics: ui::LyricsState {
+                visible: false,
+                path: None,
+                lines: Vec::new(),
+                request: None,
+                requested: false,
+                fetching: false,
+            },
+            video: ui::VideoState {
+                enabled: false,
+                enabled_for: None,
+                reloading: None,
+            },
+            segments: ui::SegmentSkipState {
+                path: None,
+                list: Vec::new(),
+                request: None,
+                requested: false,
+                skipped: HashSet::new(),
+            },
+            artist_radio: None,
+            playlist_feed: None,
+            community: CONFIG.community.clone(),
+            trending: ui::TrendingState {
+                region: CONFIG.constants.region.clone(),
+                category: fetcher::TrendingCategory::Music,
+                picker: 0,
+            },
+            downloads: 0,
+            autoplay: ui::AutoplayState {
+                enabled: CONFIG.playback.autoplay,
+                request: None,
+                requested_for: None,
+                history: HashSet::new(),
+            },
+        };
+
+        state.set_loudness_mode(CONFIG.playback.loudness);
+        if CONFIG.playback.video {
+            state.video.enabled = state.player.set_video(true);
+        }
+        // Active preset is already validated to exist while loading config
+        if let Some(index) = CONFIG
+            .audio_filters
+            .position_of(&CONFIG.audio_filters.active)
+        {
+            state.set_audio_preset(index);
         }
+        state
     }
 }
 
+// Operations needed from player backend. Backend only need to implement the first three
+// primitive method and rest of operations are built on top of them so that all backends behave
+// same. Backend may still override any method if it can do that in better way.
 pub trait ExtendMpv {
-    fn configure_defult(&self);
-    fn repeat_playlist(&self);
-    fn repeat_one(&self);
-    fn repeat_nothing(&self);
-    fn shuffle(&self);
-    fn unshuffle(&self);
-    fn cache_for(&self, time: i64);
-    fn play_next(&self);
-    fn play_prev(&self);
-    fn change_volume(&self, step: i8) -> Option<u8>;
-    fn get_volume(&self) -> Option<f64>;
-}
-
-impl ExtendMpv for libmpv::Mpv {
-    fn configure_defult(&self) {
-        let config_dir = config::ConfigContainer::get_config_dir().unwrap();
+    // Set the property to the value. Value is in same format as written in mpv.conf
+    fn set_prop(&self, name: &str, value: &str) -> bool;
+    // Get the value of property formatted as string. None if property is not available
+    fn get_prop(&self, name: &str) -> Option<String>;
+    // Run the mpv input command. eg: run_command("playlist-next", &["weak"])
+    fn run_command(&self, name: &str, args: &[&str]) -> bool;
+
+    fn get_prop_f64(&self, name: &str) -> Option<f64> {
+        self.get_prop(name)?.trim().parse().ok()
+    }
 
-        self.set_property("config-dir", config_dir.to_str().unwrap())
-            .unwrap();
-        let mpv_config_path = config_dir.join(config::MPV_OPTION_FILE_NAME);
-        self.set_property("include", mpv_config_path.to_str().unwrap())
-            .unwrap();
+    fn get_prop_i64(&self, name: &str) -> Option<i64> {
+        self.get_prop_f64(name).map(|val| val as i64)
+    }
 
-        // Video is always hidden. Override config file
-        self.set_property("video", "no").unwrap();
+    fn configure_defult(&self) {
+        // Video is hidden to start with. Override config file. See `ui::VideoState`
+        self.set_prop("video", "no");
     }
 
-    #[inline(always)]
-    fn shuffle(&self) {
-        self.command("playlist-shuffle", &[]).ok();
+    // Show the video in seperate window of player or play audio only
+    fn set_video(&self, show: bool) -> bool {
+        self.set_prop("video", if show { "auto" } else { "no" })
     }
 
-    #[inline(always)]
-    fn unshuffle(&self) {
-        self.command("playlist-unshuffle", &[]).ok();
+    // Play without gap between tracks and let mpv start loading next item before current ends.
+    // Prefetch only works for direct stream urls. See `ui::PrefetchState`
+    fn enable_gapless(&self) {
+        self.set_prop("gapless-audio", "yes");
+        self.set_prop("prefetch-playlist", "yes");
     }
 
     #[inline(always)]
     fn cache_for(&self, time: i64) {
-        self.set_property("cache-secs", time).ok();
+        self.set_prop("cache-secs", &time.to_string());
     }
 
     #[inline(always)]
     fn get_volume(&self) -> Option<f64> {
-        self.get_property("volume").ok()
+        self.get_prop_f64("volume")
     }
 
     #[inline(always)]
@@ -601,7 +928,7 @@ impl ExtendMpv for libmpv::Mpv {
                     new_vol = 100.0;
                 }
 
-                let sucess = self.set_property("volume", new_vol).is_ok();
+                let sucess = self.set_prop("volume", &new_vol.to_string());
                 if sucess {
                     Some(new_vol as u8)
                 } else {
@@ -614,91 +941,479 @@ impl ExtendMpv for libmpv::Mpv {
 
     #[inline(always)]
     fn repeat_playlist(&self) {
-        self.set_property("loop-playlist", "inf").ok();
+        self.set_prop("loop-playlist", "inf");
     }
 
     fn repeat_nothing(&self) {
-        self.set_property("loop-playlist", "no").ok();
-        self.set_property("loop-file", "no").ok();
+        self.set_prop("loop-playlist", "no");
+        self.set_prop("loop-file", "no");
     }
 
     #[inline(always)]
     fn repeat_one(&self) {
-        self.set_property("loop-file", "inf").ok();
+        self.set_prop("loop-file", "inf");
+    }
+
+    fn set_repeat(&self, mode: ui::queue::RepeatMode) {
+        self.repeat_nothing();
+        match mode {
+            ui::queue::RepeatMode::Off => {}
+            ui::queue::RepeatMode::One => self.repeat_one(),
+            ui::queue::RepeatMode::All => self.repeat_playlist(),
+        }
+    }
+
+    // Move the queue item at `from` so that it takes the place of item at `to`
+    #[inline(always)]
+    fn move_entry(&self, from: usize, to: usize) -> bool {
+        self.run_command("playlist-move", &[&from.to_string(), &to.to_string()])
     }
 
     #[inline(always)]
     fn play_next(&self) {
-        self.playlist_next_weak().ok();
+        self.run_command("playlist-next", &["weak"]);
     }
 
     #[inline(always)]
     fn play_prev(&self) {
-        self.playlist_previous_weak().ok();
+        self.run_command("playlist-prev", &["weak"]);
+    }
+
+    // Replace the current playback with url or add it to the end of queue if append is true
+    #[inline(always)]
+    fn load_url(&self, url: &str, append: bool) -> bool {
+        if append {
+            self.run_command("loadfile", &[url, "append"])
+        } else {
+            self.run_command("loadfile", &[url])
+        }
+    }
+
+    #[inline(always)]
+    fn set_pause(&self, pause: bool) -> bool {
+        self.set_prop("pause", if pause { "yes" } else { "no" })
+    }
+
+    // Seek relative to current position. Negative value seeks backward
+    #[inline(always)]
+    fn seek(&self, secs: f64) -> bool {
+        self.run_command("seek", &[&secs.to_string(), "relative"])
+    }
+
+    // Seek to absolute position of the track
+    #[inline(always)]
+    fn seek_to(&self, secs: f64) -> bool {
+        self.run_command("seek", &[&secs.to_string(), "absolute"])
+    }
+
+    // Add the audio filter with given label replacing the previous filter of same label. If filter
+    // is None, only remove the previous one. Labels keep the filters added by us independent of each
+    // other and of the ones set by user in mpv.conf
+    fn set_audio_filter(&self, label: &str, filter: Option<&str>) -> bool {
+        // Removing the label that do not exist is error in mpv. It is fine to ignore
+        self.run_command("af", &["remove", &format!("@{}", label)]);
+        match filter {
+            Some(filter) => self.run_command("af", &["add", &format!("@{}:{}", label, filter)]),
+            None => true,
+        }
+    }
+
+    // Set playback speed without changing the pitch. mpv would otherwise pick any of scaletempo
+    // filter by itself so add scaletempo2 explicitly. Normal speed do not need any filter
+    fn set_speed(&self, speed: f64) -> bool {
+        let tempo_filter = if (speed - 1.0).abs() < f64::EPSILON {
+            None
+        } else {
+            Some("scaletempo2")
+        };
+
+        self.set_audio_filter(TEMPO_FILTER_LABEL, tempo_filter)
+            && self.set_prop("speed", &speed.to_string())
+    }
+
+    // Change speed relative to current speed. Resulting speed is rounded to two decimal place
+    // so that repeated steps do not accumulate floating point error
+    fn change_speed(&self, step: f64) -> Option<f64> {
+        let current = self.get_prop_f64("speed")?;
+        let new_speed = (((current + step) * 100.0).round() / 100.0).clamp(MIN_SPEED, MAX_SPEED);
+
+        if self.set_speed(new_speed) {
+            Some(new_speed)
+        } else {
+            None
+        }
+    }
+
+    // Replace the queue item at position with url without changing the order of queue.
+    // New url is appended and moved in place of old one and then old one is removed
+    fn replace_entry(&self, position: i64, url: &str) -> bool {
+        let count = match self.get_prop_i64("playlist-count") {
+            Some(count) if position < count => count,
+            _ => return false,
+        };
+
+        self.load_url(url, true)
+            && self.run_command(
+                "playlist-move",
+                &[&count.to_string(), &position.to_string()],
+            )
+            && self.run_command("playlist-remove", &[&(position + 1).to_string()])
+    }
+
+    // Same as `replace_entry` but for the item that is playing. New one is started before
+    // removing the old one as otherwise player moves on to next item
+    fn replace_playing(&self, position: i64, url: &str) -> bool {
+        let count = match self.get_prop_i64("playlist-count") {
+            Some(count) if position < count => count,
+            _ => return false,
+        };
+
+        self.load_url(url, true)
+            && self.run_command(
+                "playlist-move",
+                &[&count.to_string(), &position.to_string()],
+            )
+            && self.run_command("playlist-play-index", &[&position.to_string()])
+            && self.run_command("playlist-remove", &[&(position + 1).to_string()])
+    }
+}
+
+// Extract the youtube video id from the url of player queue item. Returns None for any other url
+// Urls are of form https://www.youtube.com/watch?v=<id> as we add them in queue or
+// ytdl://<id> or https://youtu.be/<id> when expanded from playlist by ytdl hook
+pub fn youtube_id_of(url: &str) -> Option<&str> {
+    let id = if let Some((_, query)) = url.split_once("youtube.com/watch?") {
+        query
+            .split('&')
+            .find_map(|param| param.strip_prefix("v="))?
+    } else if let Some(id) = url.strip_prefix("ytdl://") {
+        id
+    } else {
+        url.split_once("youtu.be/")?.1
+    };
+
+    let id = id.split(['?', '&', '#']).next().unwrap_or_default();
+    if id.is_empty() {
+        None
+    } else {
+        Some(id)
+    }
+}
+
+// Count shown in list. Count is 0 when it is not known. eg: for predefined channels
+fn count_text(count: u64) -> String {
+    if count == 0 {
+        "-".to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+// Short form of large count. eg: 1234567 -> 1.2M
+pub fn short_count(count: u64) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}K", count as f64 / 1e3),
+        1_000_000..=999_999_999 => format!("{:.1}M", count as f64 / 1e6),
+        _ => format!("{:.1}B", count as f64 / 1e9),
+    }
+}
+
+// How long ago was the given unix timestamp. eg: "3 years ago"
+pub fn time_ago(timestamp: u64) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let secs = now.saturating_sub(timestamp);
+
+    let (count, unit) = [
+        (365 * 24 * 60 * 60, "year"),
+        (30 * 24 * 60 * 60, "month"),
+        (7 * 24 * 60 * 60, "week"),
+        (24 * 60 * 60, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ]
+    .iter()
+    .find(|(unit_secs, _)| secs >= *unit_secs)
+    .map(|(unit_secs, unit)| (secs / unit_secs, *unit))
+    .unwrap_or((0, "minute"));
+
+    if count == 1 {
+        format!("1 {} ago", unit)
+    } else if count == 0 {
+        "just now".to_string()
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
+
+// Status shown after skipping the segment of given category. See `config::SEGMENT_CATEGORIES`
+fn skip_message(category: &str) -> &'static str {
+    match category {
+        "sponsor" => "Skipped sponsor..",
+        "selfpromo" => "Skipped self promotion..",
+        "interaction" => "Skipped interaction reminder..",
+        "intro" => "Skipped intro..",
+        "outro" => "Skipped outro..",
+        "preview" => "Skipped preview..",
+        "music_offtopic" => "Skipped non-music part..",
+        "filler" => "Skipped filler..",
+        _ => "Skipped segment..",
+    }
+}
+
+// Read the lrc file of playing track if there is one. For local file it is the file with same name
+// but lrc extension. For youtube video it is any lrc file in download directory whose name ends
+// with the video id as youtube-dl names the downloads as "<title>-<id>.<ext>"
+fn local_lyrics(path: &str, video_id: Option<&str>) -> Option<Vec<fetcher::lyrics::LyricLine>> {
+    let lrc_path = match video_id {
+        Some(video_id) => {
+            let suffix = format!("{}.lrc", video_id);
+            std::fs::read_dir(&CONFIG.download.path)
+                .ok()?
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .find(|file| {
+                    file.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.ends_with(&suffix))
+                })?
+        }
+        None => std::path::Path::new(path).with_extension("lrc"),
+    };
+
+    let lines = fetcher::lyrics::parse_lrc(&std::fs::read_to_string(lrc_path).ok()?);
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines)
+    }
+}
+
+#[cfg(feature = "libmpv")]
+impl ExtendMpv for libmpv::Mpv {
+    fn set_prop(&self, name: &str, value: &str) -> bool {
+        self.set_property(name, value).is_ok()
+    }
+
+    fn get_prop(&self, name: &str) -> Option<String> {
+        self.get_property::<String>(name).ok()
+    }
+
+    fn run_command(&self, name: &str, args: &[&str]) -> bool {
+        self.command(name, args).is_ok()
+    }
+
+    fn get_prop_f64(&self, name: &str) -> Option<f64> {
+        self.get_property::<f64>(name).ok()
+    }
+
+    fn get_prop_i64(&self, name: &str) -> Option<i64> {
+        self.get_property::<i64>(name).ok()
+    }
+
+    fn configure_defult(&self) {
+        let config_dir = config::ConfigContainer::get_config_dir().unwrap();
+
+        self.set_property("config-dir", config_dir.to_str().unwrap())
+            .unwrap();
+        let mpv_config_path = config_dir.join(config::MPV_OPTION_FILE_NAME);
+        self.set_property("include", mpv_config_path.to_str().unwrap())
+            .unwrap();
+
+        // Video is hidden to start with. Override config file. See `ui::VideoState`
+        self.set_property("video", "no").unwrap();
+    }
+}
+
+// Create the player backend as selected in `MpvOptions::backend` of config.
+// Any failure here is hard error as nothing can be played without the player
+fn spawn_player() -> Box<dyn ExtendMpv + Send> {
+    match CONFIG.mpv.backend {
+        config::PlayerBackend::Libmpv => {
+            #[cfg(feature = "libmpv")]
+            {
+                Box::new(libmpv::Mpv::new().unwrap())
+            }
+            #[cfg(not(feature = "libmpv"))]
+            {
+                eprintln!("This build of ytui-music is compiled without libmpv.");
+                eprintln!("Set `backend` to \"ipc\" in `MpvOptions` of config file to use mpv binary instead.");
+                std::process::exit(1);
+            }
+        }
+        config::PlayerBackend::Ipc => {
+            #[cfg(unix)]
+            {
+                let config_dir = config::ConfigContainer::get_config_dir().unwrap();
+                let mpv_config_path = config_dir.join(config::MPV_OPTION_FILE_NAME);
+                let args = [
+                    format!("--config-dir={}", config_dir.to_string_lossy()),
+                    format!("--include={}", mpv_config_path.to_string_lossy()),
+                ];
+                match ui::mpv_ipc::MpvIpc::spawn(&CONFIG.mpv.mpv_binary, &args) {
+                    Ok(mpv) => Box::new(mpv),
+                    Err(err) => {
+                        eprintln!(
+                            "Cannot start mpv from `{binary}`. Error: {err}",
+                            binary = CONFIG.mpv.mpv_binary,
+                            err = err
+                        );
+                        std::process::exit(1);
+                    }
+                }
+            }
+            #[cfg(not(unix))]
+            {
+                eprintln!("Ipc backend for mpv is only supported in unix like systems.");
+                std::process::exit(1);
+            }
+        }
     }
 }
 
 impl ui::State<'_> {
     pub fn play_music(&mut self, music_id: &str) {
-        self.player.unpause().ok();
-        match self.player.command(
-            "loadfile",
-            [format!("https://www.youtube.com/watch?v={}", music_id).as_str()].as_ref(),
-        ) {
-            Ok(_) => {
-                // clear any previous thing from bottombar
-                self.bottom.music_duration = Duration::from_secs(0);
-                self.bottom.music_elapse = Duration::from_secs(0);
-
-                self.status = "Playing...";
-                // set currently playing (unpaused) to ture. no need to set real title as it will
-                // be done by refresh_mpv_status() later on
-                self.bottom.playing = Some((String::new(), true))
-            }
-            Err(_) => self.status = "Playback error..",
+        self.player.set_pause(false);
+        let url = format!("https://www.youtube.com/watch?v={}", music_id);
+        if self.player.load_url(&url, false) {
+            self.on_queue_replaced();
+            self.status = "Playing...";
+        } else {
+            self.status = "Playback error..";
         }
         // Now as the selection is being played. Add remaining item from musicbar to the play
         // queue.
         for music in self.musicbar.0.iter() {
+            if music.live {
+                self.bottom.known_live.insert(music.id.clone());
+            }
             // If this is the currently payed song donot add it to prevent having
             // currently played song two time in queue
             if music.id == *music_id {
                 continue;
             }
-            self.player
-                .command(
-                    "loadfile",
-                    [
-                        format!("https://www.youtube.com/watch?v={}", music.id).as_str(),
-                        "append",
-                    ]
-                    .as_ref(),
-                )
-                .ok();
+            self.player.load_url(
+                &format!("https://www.youtube.com/watch?v={}", music.id),
+                true,
+            );
         }
     }
 
-    // This function is called when user press enter in non-empty list of playlistbar
+    // This function is called when user press enter in non-empty list of playlistbar.
+    // Queue is not replaced right away. It is done once the first page of playlist is fetched
     pub fn activate_playlist(&mut self, playlist_id: &str) {
-        match self.player.command(
-            "loadfile",
-            [format!("https://www.youtube.com/playlist?list={}", playlist_id).as_str()].as_ref(),
-        ) {
-            Ok(_) => {
-                // send unpause signal
-                self.player.unpause().ok();
-
-                // clear any previous thing from bottombar
-                self.bottom.music_duration = Duration::from_secs(0);
-                self.bottom.music_elapse = Duration::from_secs(0);
+        self.playlist_feed = Some(ui::PlaylistFeed {
+            id: playlist_id.to_string(),
+            request: Some(0),
+            awaiting: 0,
+        });
+        self.status = "Loading playlist..";
+    }
 
-                self.status = "Playing..";
-                // set currently playing (unpaused) to ture. no need to set real title as it will
-                // be done by refresh_mpv_status() later on
-                self.bottom.playing = Some((String::new(), true));
+    // Called by communicator with a page of playlist being fed to the queue. First page replaces
+    // the queue and starts playing while later pages are appended. Next page is requested right
+    // away until whole playlist is in the queue
+    pub fn use_playlist_page(
+        &mut self,
+        playlist_id: &str,
+        page: usize,
+        music: Result<Vec<fetcher::MusicUnit>, fetcher::ReturnAction>,
+    ) {
+        let mut feed = match self.playlist_feed.take() {
+            Some(feed) if feed.id == playlist_id && feed.awaiting == page => feed,
+            // Some other playlist was activated or queue was replaced meanwhile
+            other => {
+                self.playlist_feed = other;
+                return;
+            }
+        };
+
+        let music = match music {
+            Ok(music) => music,
+            // Whole playlist is in the queue. Nothing to tell if it ended after first page
+            Err(fetcher::ReturnAction::EOR) if page > 0 => return,
+            Err(fetcher::ReturnAction::EOR) => {
+                self.status = "Empty playlist..";
+                return;
+            }
+            // Whatever is already in queue keeps playing
+            Err(_) => {
+                self.status = "Playlist fetch error..";
+                return;
             }
-            Err(_) => self.status = "Playback error..",
+        };
+
+        for music in music.iter().filter(|music| music.live) {
+            self.bottom.known_live.insert(music.id.clone());
+        }
+        let mut urls = music
+            .iter()
+            .map(|music| format!("https://www.youtube.com/watch?v={}", music.id));
+
+        if page == 0 {
+            let first = match urls.next() {
+                Some(first) => first,
+                None => return,
+            };
+            // send unpause signal
+            self.player.set_pause(false);
+            if !self.player.load_url(&first, false) {
+                self.status = "Playback error..";
+                return;
+            }
+            self.on_queue_replaced();
+            self.status = "Playing..";
+        }
+        for url in urls {
+            self.player.load_url(&url, true);
+        }
+
+        feed.awaiting = page + 1;
+        feed.request = Some(page + 1);
+        self.playlist_feed = Some(feed);
+    }
+
+    // Called by communicator with the music collected for artist radio. Music is played in
+    // random order replacing the current queue
+    pub fn start_artist_radio(&mut self, mut mix: Vec<fetcher::MusicUnit>) {
+        mix.shuffle(&mut rand::thread_rng());
+        let mut urls = mix
+            .iter()
+            .map(|music| format!("https://www.youtube.com/watch?v={}", music.id));
+
+        let first = match urls.next() {
+            Some(first) => first,
+            None => return,
+        };
+        self.player.set_pause(false);
+        if !self.player.load_url(&first, false) {
+            self.status = "Playback error..";
+            return;
+        }
+        self.on_queue_replaced();
+        for url in urls {
+            self.player.load_url(&url, true);
         }
+        self.status = "Artist radio..";
+    }
+
+    // Reset everything that depends on previous queue after it is replaced with new one
+    fn on_queue_replaced(&mut self) {
+        // Rest of playlist should not be appended to whatever is now in queue
+        self.playlist_feed = None;
+        // clear any previous thing from bottombar
+        self.bottom.music_duration = Duration::from_secs(0);
+        self.bottom.music_elapse = Duration::from_secs(0);
+        // Queue is replaced so position being same do not mean track is same
+        self.prefetch.playlist_pos = None;
+        self.queue = ui::queue::Queue::new(0, 0);
+
+        // set currently playing (unpaused) to ture. no need to set real title as it will
+        // be done by refresh_mpv_status() later on
+        self.bottom.playing = Some((String::new(), true));
     }
 
     // This function can also be used to check playing status
@@ -709,11 +1424,12 @@ impl ui::State<'_> {
         // and also we are updating the ui anway so it may also be affordable to just query mpv in
         // ui updating loop
         if let Some((_, true)) = self.bottom.playing {
-            match self.player.get_property::<i64>("audio-pts") {
-                Ok(time) => {
-                    self.bottom.music_elapse = Duration::from_secs(time as u64);
+            let audio_pts = self.player.get_prop_f64("audio-pts");
+            match audio_pts {
+                Some(time) => {
+                    self.bottom.music_elapse = Duration::from_secs_f64(time.max(0.0));
                 }
-                Err(_e) => {
+                None => {
                     // This error is generally expected to be -10 (property exist but not available
                     // at the moment)
                     // which means that the mpv has not yet loaded the file
@@ -727,58 +1443,1071 @@ impl ui::State<'_> {
             // title of the music that is being playing even from playlist so we there is no need
             // to listen to mpv event for playlist index change just to change the title and
             // duration of currently playing music.
-            let title = self
-                .player
-                .get_property::<String>("media-title")
-                .unwrap_or(">> Play some music <<".to_string());
-            let estimated_duration_reply = self
-                .player
-                .get_property::<i64>("duration")
-                .unwrap_or_default();
+            // Path is needed to know which track is playing. eg: to recognize the stream resolved
+            // by us or to keep chapters of the track
+            let track_gain = self.playback_behaviour.loudness == config::LoudnessMode::TrackGain;
+            let playing_path = self.player.get_prop("path");
+
+            // Stream resolved by us do not have proper media-title so use the title we got while
+            // resolving it
+            let resolved = playing_path
+                .as_ref()
+                .and_then(|path| self.prefetch.resolved.get(path));
+            let title = match resolved {
+                Some(stream) => stream.title.clone(),
+                None => self
+                    .player
+                    .get_prop("media-title")
+                    .unwrap_or(">> Play some music <<".to_string()),
+            };
+            let duration_reply = self.player.get_prop_f64("duration");
+            let estimated_duration_reply = duration_reply.unwrap_or_default() as i64;
+
+            // Player do not know the duration of live stream once it is loaded. Also check the
+            // list it was played from as player may report the buffered part as duration
+            let known_live = playing_path
+                .as_deref()
+                .and_then(|path| self.video_id_of(path))
+                .is_some_and(|video_id| self.bottom.known_live.contains(&video_id));
+            self.bottom.live = known_live || (duration_reply.is_none() && audio_pts.is_some());
 
             self.bottom.playing = Some((title, true)); // at this scope of match playing status is always true
             self.bottom.music_duration =
                 Duration::from_secs(estimated_duration_reply.try_into().unwrap_or_default());
+
+            self.refresh_queue();
+            self.refresh_video(playing_path.as_deref());
+            self.refresh_prefetch();
+            self.refresh_chapters(playing_path.as_deref());
+
+            let playing_id = playing_path
+                .as_deref()
+                .and_then(|path| self.video_id_of(path));
+            if track_gain {
+                self.refresh_track_gain(playing_id.clone());
+            }
+            if CONFIG.segment_skip.enabled {
+                self.refresh_segments(playing_path.as_deref());
+                self.skip_segment();
+            }
+            if self.lyrics.visible {
+                self.refresh_lyrics(playing_path.as_deref());
+            }
+            if self.autoplay.enabled {
+                self.refresh_autoplay(playing_id);
+            }
         }
     }
 
-    pub fn toggle_pause(&mut self) {
-        if let Some((_, ref mut is_playing)) = self.bottom.playing {
-            if *is_playing {
-                self.status = "Paused..";
-                self.player.pause().unwrap();
-            } else {
-                self.status = "Playing..";
-                self.player.unpause().unwrap();
+    // Keep the play order in sync with player queue. See `ui::queue::Queue`
+    fn refresh_queue(&mut self) {
+        let count = self
+            .player
+            .get_prop_i64("playlist-count")
+            .unwrap_or_default();
+        let position = match self.player.get_prop_i64("playlist-pos") {
+            Some(position) if position >= 0 => position as usize,
+            _ => return,
+        };
+
+        let len = self.queue.len() as i64;
+        if len > 0 && len < count {
+            // Items were appended. eg: Next page of playlist, autoplay or playlist url expanded to
+            // it's items by ytdl hook. Unplayed part takes them without reshuffling. Replaced
+            // queue is always emptied first. See `on_queue_replaced`
+            self.queue.set_position(position);
+            let moves = self
+                .queue
+                .extend((count - len) as usize, &mut rand::thread_rng());
+            self.apply_queue_moves(&moves);
+        } else if len != count {
+            // Queue is replaced or items were removed
+            self.queue = ui::queue::Queue::new(count.max(0) as usize, position);
+            if self.playback_behaviour.shuffle {
+                let moves = self.queue.shuffle(&mut rand::thread_rng());
+                self.apply_queue_moves(&moves);
+            }
+        } else if self.queue.position() != position {
+            self.queue.set_position(position);
+            if self.playback_behaviour.repeat == ui::queue::RepeatMode::All {
+                let moves = self.queue.prepare_next_round(&mut rand::thread_rng());
+                self.apply_queue_moves(&moves);
             }
-            *is_playing = !*is_playing;
         }
     }
-}
-
-impl ui::State<'_> {
-    pub fn remove_music_from_favourates(&mut self, music: &fetcher::MusicUnit) {
-        let query = format!(
-            "
-            DELETE FROM
-            {tb_name} as fav_music
-            WHERE
-            fav_music.id = :id
-        ",
-            tb_name = TB_FAVOURATES_MUSIC
-        );
-        let args = [(":id", &music.id)];
 
-        let res = STORAGE.lock().unwrap().execute(&query, &args);
-        if res.is_ok() {
-            self.status = "Removed..";
-        } else {
-            self.status = "Err removing..";
+    fn apply_queue_moves(&mut self, moves: &[ui::queue::Move]) {
+        for &(from, to) in moves {
+            if !self.player.move_entry(from, to) {
+                self.status = "Queue error..";
+                return;
+            }
         }
     }
 
-    pub fn remove_playlist_from_favourates(&mut self, playlist: &fetcher::PlaylistUnit) {
-        let query = format!(
+    pub fn toggle_shuffle(&mut self) {
+        // Make sure queue is up to date before rearranging it
+        self.refresh_queue();
+        self.playback_behaviour.shuffle = !self.playback_behaviour.shuffle;
+
+        let moves = if self.playback_behaviour.shuffle {
+            self.queue.shuffle(&mut rand::thread_rng())
+        } else {
+            self.queue.unshuffle()
+        };
+        self.apply_queue_moves(&moves);
+    }
+
+    pub fn cycle_repeat(&mut self) {
+        self.playback_behaviour.repeat = self.playback_behaviour.repeat.next();
+        self.player.set_repeat(self.playback_behaviour.repeat);
+    }
+
+    // When track changes, ask communicator to resolve the next item of queue.
+    // See `ui::PrefetchState`
+    fn refresh_prefetch(&mut self) {
+        // Resolved stream is audio only. Let ytdl hook pick the video when it is to be shown
+        if self.video.enabled && self.video.enabled_for.is_none() {
+            return;
+        }
+
+        let playlist_pos = self.player.get_prop_i64("playlist-pos");
+        if playlist_pos == self.prefetch.playlist_pos {
+            return;
+        }
+        self.prefetch.playlist_pos = playlist_pos;
+
+        let next_position = match playlist_pos {
+            Some(pos) if pos >= 0 => pos + 1,
+            _ => return,
+        };
+        let next_url = self
+            .player
+            .get_prop(&format!("playlist/{}/filename", next_position));
+
+        self.prefetch.request = match next_url.as_deref().and_then(youtube_id_of) {
+            // Live stream only have hls manifest which player resolves by itself
+            Some(video_id) if self.bottom.known_live.contains(video_id) => ui::Prefetch::Idle,
+            Some(video_id) => ui::Prefetch::Request {
+                position: next_position,
+                video_id: video_id.to_string(),
+                attempt: 0,
+            },
+            None => ui::Prefetch::Idle,
+        };
+    }
+
+    // Called by communicator once the requested stream is resolved
+    pub fn use_prefetched(&mut self, position: i64, stream: fetcher::AudioStream) {
+        // If track have changed meanwhile, painter would have already placed new request
+        if self.prefetch.request == ui::Prefetch::Pending {
+            self.prefetch.request = ui::Prefetch::Idle;
+        }
+
+        // Queue may have changed while the stream was being resolved. eg: user played another
+        // music. Make sure that same item is still there
+        let current_url = self
+            .player
+            .get_prop(&format!("playlist/{}/filename", position));
+        if current_url.as_deref().and_then(youtube_id_of) != Some(stream.id.as_str()) {
+            return;
+        }
+
+        if self.player.replace_entry(position, &stream.url) {
+            // Only keep the currently playing stream and the new one
+            let playing_path = self.player.get_prop("path").unwrap_or_default();
+            self.prefetch.resolved.retain(|url, _| *url == playing_path);
+            // Loudness also comes with the stream. Save it so that it need not be fetched again
+            self.track_gain
+                .known
+                .insert(stream.id.clone(), stream.loudness_db);
+            self.prefetch.resolved.insert(stream.url.clone(), stream);
+        } else {
+            self.status = "Prefetch failed..";
+        }
+    }
+
+    pub fn set_loudness_mode(&mut self, mode: config::LoudnessMode) {
+        self.playback_behaviour.loudness = mode;
+        // So that gain of current track is applied again in next refresh if mode is TrackGain
+        self.track_gain.applied_for = None;
+
+        let filter = match mode {
+            config::LoudnessMode::Off | config::LoudnessMode::TrackGain => None,
+            config::LoudnessMode::Loudnorm => Some(LOUDNORM_FILTER),
+            config::LoudnessMode::Dynaudnorm => Some(DYNAUDNORM_FILTER),
+        };
+        if !self.player.set_audio_filter(LOUDNESS_FILTER_LABEL, filter) {
+            self.status = "Filter error..";
+        }
+    }
+
+    pub fn set_audio_preset(&mut self, index: usize) {
+        if let Some(preset) = CONFIG.audio_filters.presets.get(index) {
+            self.playback_behaviour.audio_preset = Some(index);
+            if !self
+                .player
+                .set_audio_filter(EQUALIZER_FILTER_LABEL, preset.to_filter().as_deref())
+            {
+                self.status = "Filter error..";
+            }
+        }
+    }
+
+    pub fn cycle_audio_preset(&mut self) {
+        let preset_count = CONFIG.audio_filters.presets.len();
+        if let Some(index) = self.playback_behaviour.audio_preset {
+            self.set_audio_preset((index + 1) % preset_count);
+        }
+    }
+
+    // When track changes, apply the gain if loudness of track is known or ask communicator to
+    // fetch it. See `ui::TrackGainState`
+    fn refresh_track_gain(&mut self, playing_id: Option<String>) {
+        let video_id = match playing_id {
+            Some(video_id) => video_id,
+            None => return,
+        };
+
+        if self.track_gain.applied_for.as_ref() == Some(&video_id) {
+            return;
+        }
+
+        match self.track_gain.known.get(&video_id) {
+            Some(&loudness_db) => {
+                self.apply_track_gain(loudness_db);
+                // Forget the tracks played before so this do not grow forever. Loudness of next
+                // track is added again when it is prefetched after this
+                self.track_gain.known.retain(|id, _| *id == video_id);
+                self.track_gain.applied_for = Some(video_id);
+            }
+            None if self.track_gain.requested_for.as_ref() != Some(&video_id) => {
+                self.track_gain.requested_for = Some(video_id.clone());
+                self.track_gain.request = Some(video_id);
+            }
+            None => {}
+        }
+    }
+
+    // Reset the chapters when track changes and fill them once they are known. See `ui::ChapterState`
+    fn refresh_chapters(&mut self, playing_path: Option<&str>) {
+        let path = match playing_path {
+            Some(path) => path,
+            None => return,
+        };
+        if self.chapters.path.as_deref() != Some(path) {
+            self.chapters.path = Some(path.to_string());
+            self.chapters.list.clear();
+            self.chapters.request = None;
+            self.chapters.requested = false;
+        }
+        if !self.chapters.list.is_empty() {
+            return;
+        }
+
+        // Player knows the chapters only after the track is loaded. So check until then
+        let count = self
+            .player
+            .get_prop_i64("chapter-list/count")
+            .unwrap_or_default();
+        if count > 0 {
+            self.chapters.list = (0..count)
+                .map(|index| fetcher::Chapter {
+                    title: self
+                        .player
+                        .get_prop(&format!("chapter-list/{}/title", index))
+                        .unwrap_or_default(),
+                    start: Duration::from_secs_f64(
+                        self.player
+                            .get_prop_f64(&format!("chapter-list/{}/time", index))
+                            .unwrap_or_default()
+                            .max(0.0),
+                    ),
+                })
+                .collect();
+            self.chapters.request = None;
+        } else if !self.chapters.requested {
+            self.chapters.requested = true;
+            self.chapters.request = self.video_id_of(path);
+        }
+    }
+
+    // Called by communicator with the chapters parsed from description of the video
+    pub fn use_chapters(&mut self, video_id: &str, chapters: Vec<fetcher::Chapter>) {
+        let path = match self.chapters.path.as_deref() {
+            Some(path) => path,
+            None => return,
+        };
+        // Track may have changed or player have found the chapters by itself meanwhile
+        if self.video_id_of(path).as_deref() == Some(video_id) && self.chapters.list.is_empty() {
+            self.chapters.list = chapters;
+        }
+    }
+
+    // Index of the chapter that is playing
+    pub fn current_chapter(&self) -> Option<usize> {
+        self.chapters
+            .list
+            .iter()
+            .rposition(|chapter| chapter.start <= self.bottom.music_elapse)
+    }
+
+    // Seek relative to current position. Live stream cannot be seeked
+    pub fn seek(&mut self, secs: f64) {
+        if self.bottom.live {
+            self.status = "Live stream..";
+        } else if !self.player.seek(secs) {
+            self.status = "Seek error..";
+        }
+    }
+
+    // Seek to start of next or previous chapter. Going to previous chapter when few seconds have
+    // already been played from current chapter only restarts the current chapter
+    pub fn change_chapter(&mut self, forward: bool) {
+        if self.bottom.live {
+            self.status = "Live stream..";
+            return;
+        }
+        let target = match (self.current_chapter(), forward) {
+            (None, true) => Some(0),
+            (None, false) => None,
+            (Some(current), true) => Some(current + 1),
+            (Some(current), false) => {
+                let played = self
+                    .bottom
+                    .music_elapse
+                    .saturating_sub(self.chapters.list[current].start);
+                if played > Duration::from_secs(3) {
+                    Some(current)
+                } else {
+                    current.checked_sub(1)
+                }
+            }
+        };
+
+        match target.and_then(|target| self.chapters.list.get(target)) {
+            Some(chapter) => {
+                if !self.player.seek_to(chapter.start.as_secs_f64()) {
+                    self.status = "Seek error..";
+                }
+            }
+            None => self.status = "No more chapter..",
+        }
+    }
+
+    pub fn show_chapters(&mut self) {
+        if self.chapters.list.is_empty() {
+            self.status = "No chapters..";
+            return;
+        }
+
+        let current = self.current_chapter();
+        let content = self
+            .chapters
+            .list
+            .iter()
+            .enumerate()
+            .map(|(index, chapter)| {
+                let marker = if Some(index) == current { ">" } else { " " };
+                format!(
+                    "{} {}  {}",
+                    marker,
+                    chapter.start.to_string(),
+                    chapter.title
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.active = ui::Window::Popup("Chapters", content);
+    }
+
+    fn refresh_segments(&mut self, playing_path: Option<&str>) {
+        let path = match playing_path {
+            Some(path) => path,
+            None => return,
+        };
+        if self.segments.path.as_deref() != Some(path) {
+            self.segments.path = Some(path.to_string());
+            self.segments.list.clear();
+            self.segments.skipped.clear();
+            self.segments.request = None;
+            self.segments.requested = false;
+        }
+        if !self.segments.requested {
+            self.segments.requested = true;
+            self.segments.request = self.video_id_of(path);
+        }
+    }
+
+    // Called by communicator with the segments to be skipped
+    pub fn use_segments(&mut self, video_id: &str, segments: Vec<fetcher::SkipSegment>) {
+        let path = match self.segments.path.as_deref() {
+            Some(path) => path,
+            None => return,
+        };
+        if self.video_id_of(path).as_deref() == Some(video_id) {
+            self.segments.list = segments;
+        }
+    }
+
+    // Seek to the end of segment if it is being played
+    fn skip_segment(&mut self) {
+        if self.segments.list.is_empty() {
+            return;
+        }
+        // Do not rely on music_elapse as it still holds the time of previous track until the
+        // new one is loaded
+        let elapse = match self.player.get_prop_f64("audio-pts") {
+            Some(time) => Duration::from_secs_f64(time.max(0.0)),
+            None => return,
+        };
+
+        let playing = self
+            .segments
+            .list
+            .iter()
+            .enumerate()
+            .find(|(index, segment)| {
+                !self.segments.skipped.contains(index)
+                    // Not worth seeking when segment is just about to end
+                    && segment.start <= elapse
+                    && elapse + Duration::from_secs(1) < segment.end
+            })
+            .map(|(index, segment)| (index, segment.end, skip_message(&segment.category)));
+
+        if let Some((index, end, message)) = playing {
+            self.segments.skipped.insert(index);
+            if self.player.seek_to(end.as_secs_f64()) {
+                self.status = message;
+            } else {
+                self.status = "Seek error..";
+            }
+        }
+    }
+
+    pub fn toggle_video(&mut self) {
+        let enable = !self.video.enabled;
+        if !self.player.set_video(enable) {
+            self.status = "Video error..";
+            return;
+        }
+        self.video.enabled = enable;
+        self.video.enabled_for = None;
+        self.status = if enable { "Video on.." } else { "Video off.." };
+        if !enable {
+            return;
+        }
+
+        let playing_path = match self.player.get_prop("path") {
+            Some(path) => path,
+            None => return,
+        };
+        let position = self.player.get_prop_i64("playlist-pos").unwrap_or(-1);
+        let reload_url = self
+            .video_id_of(&playing_path)
+            .map(|video_id| format!("https://www.youtube.com/watch?v={}", video_id));
+
+        // Local files already have video if there is any
+        let new_path = match reload_url {
+            Some(url) if position >= 0 => {
+                let elapse = self.player.get_prop_f64("audio-pts").unwrap_or_default();
+                self.player
+                    .set_prop("start", &format!("{:.1}", elapse.max(0.0)));
+                if self.player.replace_playing(position, &url) {
+                    self.video.reloading = Some((url.clone(), playing_path));
+                    url
+                } else {
+                    self.player.set_prop("start", "none");
+                    self.status = "Video error..";
+                    playing_path
+                }
+            }
+            _ => playing_path,
+        };
+        if CONFIG.playback.video_current_track_only {
+            self.video.enabled_for = Some(new_path);
+        }
+    }
+
+    fn refresh_video(&mut self, playing_path: Option<&str>) {
+        let path = match playing_path {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Some((new_path, old_path)) = &self.video.reloading {
+            // Wait until player have switched to the new one and loaded it
+            let loaded = new_path == path && self.player.get_prop_f64("audio-pts").is_some();
+            if path == old_path || (new_path == path && !loaded) {
+                return;
+            }
+            self.player.set_prop("start", "none");
+            self.video.reloading = None;
+        }
+
+        let track_changed = self
+            .video
+            .enabled_for
+            .as_deref()
+            .is_some_and(|enabled_for| enabled_for != path);
+        if self.video.enabled && track_changed {
+            self.video.enabled_for = None;
+            self.video.enabled = !self.player.set_video(false);
+            self.status = "Video off..";
+        }
+    }
+
+    pub fn toggle_lyrics(&mut self) {
+        // Lyrics are looked up in next refresh of player status
+        self.lyrics.visible = !self.lyrics.visible;
+    }
+
+    // Look for the lyrics once per track. Lrc file is read right away and captions are asked
+    // from communicator. See `ui::LyricsState`
+    fn refresh_lyrics(&mut self, playing_path: Option<&str>) {
+        let path = match playing_path {
+            Some(path) => path,
+            None => return,
+        };
+        if self.lyrics.path.as_deref() != Some(path) {
+            self.lyrics.path = Some(path.to_string());
+            self.lyrics.lines.clear();
+            self.lyrics.request = None;
+            self.lyrics.requested = false;
+            self.lyrics.fetching = false;
+        }
+        if self.lyrics.requested {
+            return;
+        }
+        self.lyrics.requested = true;
+
+        let video_id = self.video_id_of(path);
+        match local_lyrics(path, video_id.as_deref()) {
+            Some(lines) => self.lyrics.lines = lines,
+            None => {
+                self.lyrics.fetching = video_id.is_some();
+                self.lyrics.request = video_id;
+            }
+        }
+    }
+
+    // Called by communicator with the lines parsed from captions of the video. Empty when video
+    // has no captions or those could not be fetched
+    pub fn use_lyrics(&mut self, video_id: &str, lines: Vec<fetcher::lyrics::LyricLine>) {
+        let path = match self.lyrics.path.as_deref() {
+            Some(path) => path,
+            None => return,
+        };
+        if self.video_id_of(path).as_deref() == Some(video_id) {
+            self.lyrics.fetching = false;
+            self.lyrics.lines = lines;
+        }
+    }
+
+    // Index of the line that is being sung
+    pub fn current_lyric(&self) -> Option<usize> {
+        self.lyrics
+            .lines
+            .iter()
+            .rposition(|line| line.start <= self.bottom.music_elapse)
+    }
+
+    // Video id of the queue item with given path. Stream resolved by us do not have the id in url
+    fn video_id_of(&self, path: &str) -> Option<String> {
+        match self.prefetch.resolved.get(path) {
+            Some(stream) => Some(stream.id.clone()),
+            None => youtube_id_of(path).map(str::to_string),
+        }
+    }
+
+    // Remember the playing track and ask for related videos if it is the last item of queue.
+    // See `ui::AutoplayState`
+    fn refresh_autoplay(&mut self, playing_id: Option<String>) {
+        let video_id = match playing_id {
+            Some(video_id) => video_id,
+            None => return,
+        };
+        self.autoplay.history.insert(video_id.clone());
+
+        // Queue never ends when repeating single track
+        if self.autoplay.requested_for.as_ref() == Some(&video_id)
+            || self.playback_behaviour.repeat == ui::queue::RepeatMode::One
+        {
+            return;
+        }
+
+        let position = self.player.get_prop_i64("playlist-pos");
+        let count = self.player.get_prop_i64("playlist-count");
+        if position.is_some() && position.map(|position| position + 1) == count {
+            self.autoplay.requested_for = Some(video_id.clone());
+            self.autoplay.request = Some(video_id);
+        }
+    }
+
+    // Called by communicator with the related videos of last track. Append the suitable ones to
+    // the queue
+    pub fn use_recommended(&mut self, recommended: Vec<fetcher::MusicUnit>) {
+        // Whatever is already in queue should not be added again
+        let count = self
+            .player
+            .get_prop_i64("playlist-count")
+            .unwrap_or_default();
+        for position in 0..count {
+            let path = self
+                .player
+                .get_prop(&format!("playlist/{}/filename", position));
+            if let Some(video_id) = path.and_then(|path| self.video_id_of(&path)) {
+                self.autoplay.history.insert(video_id);
+            }
+        }
+
+        let chosen = pick_autoplay(
+            recommended,
+            &self.autoplay.history,
+            CONFIG.playback.autoplay_min_secs,
+            CONFIG.playback.autoplay_max_secs,
+        );
+        if chosen.is_empty() {
+            self.status = "Nothing to autoplay..";
+            return;
+        }
+
+        for music in chosen {
+            let url = format!("https://www.youtube.com/watch?v={}", music.id);
+            if !self.player.load_url(&url, true) {
+                self.status = "Autoplay failed..";
+                return;
+            }
+            self.autoplay.history.insert(music.id);
+        }
+        self.status = "Autoplay..";
+    }
+
+    pub fn toggle_autoplay(&mut self) {
+        self.autoplay.enabled = !self.autoplay.enabled;
+        // Check again with current track in next refresh
+        self.autoplay.requested_for = None;
+    }
+
+    // Called by communicator once the loudness of requested track is fetched. Loudness is None
+    // if server did not report it or fetch failed. Such track is played as it is
+    pub fn use_track_gain(&mut self, video_id: String, loudness_db: Option<f64>) {
+        self.track_gain.known.insert(video_id, loudness_db);
+    }
+
+    fn apply_track_gain(&mut self, loudness_db: Option<f64>) {
+        // Positive loudness means track is louder than reference so reduce by same amount
+        let filter = loudness_db.map(|loudness_db| {
+            format!(
+                "lavfi=[volume={:.2}dB]",
+                (-loudness_db).min(MAX_TRACK_GAIN_DB)
+            )
+        });
+
+        if !self
+            .player
+            .set_audio_filter(LOUDNESS_FILTER_LABEL, filter.as_deref())
+        {
+            self.status = "Filter error..";
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if let Some((_, ref mut is_playing)) = self.bottom.playing {
+            if *is_playing {
+                self.status = "Paused..";
+                self.player.set_pause(true);
+            } else {
+                self.status = "Playing..";
+                self.player.set_pause(false);
+            }
+            *is_playing = !*is_playing;
+        }
+    }
+
+    // Called after the text of search box is changed by user. Previous completion or query being
+    // walked through is forgotten and suggestions are asked for new text
+    pub fn search_edited(&mut self) {
+        let assist = &mut self.search_assist;
+        assist.typed = self.search.0.clone();
+        assist.history_pos = None;
+        assist.completion_pos = None;
+        assist.suggestions.clear();
+        assist.edited_at = if assist.typed.trim().is_empty() {
+            None
+        } else {
+            Some(Instant::now())
+        };
+    }
+
+    // Text whose suggestions should be fetched now. It is only asked once typing stops for
+    // `SUGGESTIONS_DELAY` so that request is not sent for every keystroke
+    pub fn take_suggestions_request(&mut self) -> Option<String> {
+        let edited_at = self.search_assist.edited_at?;
+        if edited_at.elapsed() < SUGGESTIONS_DELAY {
+            return None;
+        }
+        self.search_assist.edited_at = None;
+        Some(self.search_assist.typed.clone())
+    }
+
+    // Called by communicator with the suggestions of server. Those of text that is no longer in
+    // search box are of no use
+    pub fn use_suggestions(&mut self, query: &str, suggestions: Vec<String>) {
+        if self.search_assist.typed == query {
+            self.search_assist.suggestions = suggestions;
+        }
+    }
+
+    pub fn search_completions(&self) -> Vec<&str> {
+        let assist = &self.search_assist;
+        search_completions(&assist.typed, &assist.history, &assist.suggestions)
+    }
+
+    // Index of completion currently shown in search box. None if text is as typed by user
+    pub fn completion_pos(&self) -> Option<usize> {
+        self.search_assist.completion_pos
+    }
+
+    // Show the next completion in search box. Return false if there is nothing to complete
+    pub fn complete_search(&mut self) -> bool {
+        let (pos, completion) = {
+            let completions = self.search_completions();
+            if completions.is_empty() {
+                return false;
+            }
+            let pos = match self.search_assist.completion_pos {
+                Some(pos) => (pos + 1) % completions.len(),
+                None => 0,
+            };
+            (pos, completions[pos].to_string())
+        };
+        self.search_assist.completion_pos = Some(pos);
+        self.search_assist.history_pos = None;
+        self.search.0 = completion;
+        true
+    }
+
+    // Show older (or newer) query from history in search box. Going newer than the latest brings
+    // back the text as typed by user
+    pub fn walk_search_history(&mut self, older: bool) {
+        let assist = &mut self.search_assist;
+        let pos = match (assist.history_pos, older) {
+            (None, true) if !assist.history.is_empty() => Some(0),
+            (Some(pos), true) if pos + 1 < assist.history.len() => Some(pos + 1),
+            (Some(0), false) => None,
+            (Some(pos), false) => Some(pos - 1),
+            (pos, _) => pos,
+        };
+        assist.history_pos = pos;
+        assist.completion_pos = None;
+        self.search.0 = match pos {
+            Some(pos) => assist.history[pos].clone(),
+            None => assist.typed.clone(),
+        };
+    }
+
+    // Put the searched query at top of history and save it in local database
+    pub fn remember_search(&mut self, query: &str) {
+        let assist = &mut self.search_assist;
+        assist.history.retain(|previous| previous != query);
+        assist.history.insert(0, query.to_string());
+        assist.history.truncate(MAX_SEARCH_HISTORY);
+        assist.typed = query.to_string();
+        assist.history_pos = None;
+        assist.completion_pos = None;
+        assist.suggestions.clear();
+        assist.edited_at = None;
+
+        let searched_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            .to_string();
+        let insert = format!(
+            "INSERT OR REPLACE INTO {tb_history} (query, searched_at) VALUES (:query, :searched_at)",
+            tb_history = TB_SEARCH_HISTORY
+        );
+        let args = [(":query", query), (":searched_at", searched_at.as_str())];
+        // Only latest ones are kept
+        let trim = format!(
+            "DELETE FROM {tb_history} WHERE query NOT IN (SELECT query FROM {tb_history} ORDER BY searched_at DESC LIMIT {limit})",
+            tb_history = TB_SEARCH_HISTORY,
+            limit = MAX_SEARCH_HISTORY
+        );
+
+        let conn = STORAGE.lock().unwrap();
+        let res = conn
+            .execute(&insert, &args)
+            .and_then(|_| conn.execute_batch(&trim));
+        if let Err(err) = res {
+            eprintln!("Error storing search history. Error: {err}", err = err);
+        }
+    }
+
+    // Show the regions of trending with cursor on the current one. See `REGION_PICKER_TITLE`
+    pub fn show_region_picker(&mut self) {
+        self.trending.picker = config::TRENDING_REGIONS
+            .iter()
+            .position(|region| region.eq_ignore_ascii_case(&self.trending.region))
+            .unwrap_or_default();
+        self.refresh_region_picker();
+    }
+
+    // Move the cursor of region picker by `step` wrapping around at the ends
+    pub fn move_region_picker(&mut self, step: isize) {
+        let count = config::TRENDING_REGIONS.len() as isize;
+        self.trending.picker = (self.trending.picker as isize + step).rem_euclid(count) as usize;
+        self.refresh_region_picker();
+    }
+
+    // Use the region under cursor for trending and close the picker
+    pub fn pick_region(&mut self) {
+        self.active = ui::Window::Sidebar;
+        self.trending.region = config::TRENDING_REGIONS[self.trending.picker].to_string();
+    }
+
+    fn refresh_region_picker(&mut self) {
+        let content = config::TRENDING_REGIONS
+            .iter()
+            .enumerate()
+            .map(|(index, region)| {
+                if index == self.trending.picker {
+                    format!("[{}]", region)
+                } else {
+                    format!(" {} ", region)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.active = ui::Window::Popup(REGION_PICKER_TITLE, content);
+    }
+
+    // Show options of sleep timer with cursor on the one currently set. See `SLEEP_PICKER_TITLE`
+    pub fn show_sleep_picker(&mut self) {
+        let current = self
+            .sleep_timer
+            .as_ref()
+            .map(|timer| SleepOption::of(timer.at));
+        // Minutes set from typing are shown again along with the presets
+        self.sleep_picker.typed_minutes = match current {
+            Some(SleepOption::Minutes(minutes)) => minutes,
+            _ => 0,
+        };
+        self.sleep_picker.cursor = current
+            .and_then(|current| {
+                sleep_options(self.sleep_picker.typed_minutes)
+                    .iter()
+                    .position(|&option| option == current)
+            })
+            // First option of minutes
+            .unwrap_or(1);
+        self.refresh_sleep_picker();
+    }
+
+    // Move the cursor of sleep timer picker by `step` wrapping around at the ends
+    pub fn move_sleep_picker(&mut self, step: isize) {
+        let count = sleep_options(self.sleep_picker.typed_minutes).len() as isize;
+        self.sleep_picker.cursor =
+            (self.sleep_picker.cursor as isize + step).rem_euclid(count) as usize;
+        self.refresh_sleep_picker();
+    }
+
+    // Add the digit to minutes typed in sleep timer picker and put cursor on it
+    pub fn type_sleep_minutes(&mut self, digit: char) {
+        let digit = match digit.to_digit(10) {
+            Some(digit) => digit as u64,
+            None => return,
+        };
+        let typed = self.sleep_picker.typed_minutes * 10 + digit;
+        // Start again instead of growing to something nobody means
+        self.sleep_picker.typed_minutes = if typed > MAX_SLEEP_MINUTES {
+            digit
+        } else {
+            typed
+        };
+
+        let typed = SleepOption::Minutes(self.sleep_picker.typed_minutes);
+        if let Some(cursor) = sleep_options(self.sleep_picker.typed_minutes)
+            .iter()
+            .position(|&option| option == typed)
+        {
+            self.sleep_picker.cursor = cursor;
+        }
+        self.refresh_sleep_picker();
+    }
+
+    fn refresh_sleep_picker(&mut self) {
+        let options = sleep_options(self.sleep_picker.typed_minutes)
+            .iter()
+            .enumerate()
+            .map(|(index, option)| {
+                if index == self.sleep_picker.cursor {
+                    format!("[{}]", option.describe())
+                } else {
+                    format!(" {} ", option.describe())
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.active = ui::Window::Popup(
+            SLEEP_PICKER_TITLE,
+            format!(
+                "{}\n\nType the minutes to sleep after any other time.",
+                options
+            ),
+        );
+    }
+
+    // Set the sleep timer to the option under cursor of picker
+    pub fn pick_sleep_timer(&mut self) {
+        let option = sleep_options(self.sleep_picker.typed_minutes)
+            .get(self.sleep_picker.cursor)
+            .copied()
+            .unwrap_or(SleepOption::Off);
+        let next_at = match option {
+            SleepOption::Off => None,
+            SleepOption::Minutes(minutes) => Some(ui::SleepAt::Time(
+                Instant::now() + Duration::from_secs(minutes * 60),
+                minutes,
+            )),
+            // Nothing is playing so there is no track to wait for
+            SleepOption::EndOfTrack => self
+                .player
+                .get_prop_i64("playlist-pos")
+                .map(ui::SleepAt::EndOfTrack)
+                .or(Some(ui::SleepAt::EndOfQueue(None))),
+            SleepOption::EndOfQueue => Some(ui::SleepAt::EndOfQueue(None)),
+        };
+
+        // Timer is being replaced. Restore the volume if previous one was fading it out
+        if let Some(ui::SleepTimer {
+            fading_from: Some(volume),
+            ..
+        }) = self.sleep_timer.take()
+        {
+            self.restore_volume(volume);
+        }
+
+        let message = match next_at {
+            None => "Sleep timer is turned off.".to_string(),
+            Some(ui::SleepAt::Time(_, minutes)) => format!("Sleep in {} minutes.", minutes),
+            Some(ui::SleepAt::EndOfTrack(_)) => "Sleep at the end of current track.".to_string(),
+            Some(ui::SleepAt::EndOfQueue(_)) => "Sleep at the end of queue.".to_string(),
+        };
+        let action = if CONFIG.playback.quit_on_sleep {
+            "Playback will fade out and application will quit."
+        } else {
+            "Playback will fade out and pause."
+        };
+        self.active = ui::Window::Popup(
+            "Info!",
+            format!(
+                "{} {} Press `{}` again to change.",
+                message, action, CONFIG.shortcut_keys.sleep_timer
+            ),
+        );
+
+        self.sleep_timer = next_at.map(|at| ui::SleepTimer {
+            at,
+            fading_from: None,
+        });
+    }
+
+    // Time left for sleep timer to go off. None if it cannot be known yet. eg: When sleeping at
+    // end of queue and last track is not yet playing
+    fn sleep_remaining(&self) -> Option<Duration> {
+        let timer = self.sleep_timer.as_ref()?;
+        // Duration is not known until player loads the track
+        let track_remaining = if self.bottom.music_duration.is_zero() {
+            None
+        } else {
+            Some(
+                self.bottom
+                    .music_duration
+                    .saturating_sub(self.bottom.music_elapse),
+            )
+        };
+        sleep_remaining(timer.at, &*self.player, track_remaining)
+    }
+
+    // Called by painter at least once in every refresh rate.
+    // Fade out the volume when timer is about to go off and pause the playback once it does.
+    // Return true if application should quit now. See `ui::SleepTimer`
+    pub fn tick_sleep_timer(&mut self) -> bool {
+        if let Some(timer) = self.sleep_timer.as_mut() {
+            timer.at = reach_queue_end(timer.at, &*self.player);
+        }
+        let remaining = match self.sleep_remaining() {
+            Some(remaining) => remaining,
+            None => return false,
+        };
+        let fade_duration = Duration::from_secs(CONFIG.playback.sleep_fade_secs);
+        if remaining > fade_duration {
+            return false;
+        }
+
+        let current_volume = self.playback_behaviour.volume;
+        let fading_from = match self.sleep_timer.as_mut() {
+            Some(timer) => *timer.fading_from.get_or_insert(current_volume),
+            None => return false,
+        };
+
+        // Decrease volume in proportion to the time left so that it reaches 0 when timer goes off
+        let target_volume = (fading_from as f64 * remaining.as_secs_f64()
+            / fade_duration.as_secs_f64().max(1.0)) as u8;
+        if current_volume > target_volume {
+            let step = (current_volume - target_volume).min(i8::MAX as u8) as i8;
+            if let Some(volume) = self.player.change_volume(-step) {
+                self.playback_behaviour.volume = volume;
+            }
+        }
+
+        if !remaining.is_zero() {
+            return false;
+        }
+
+        if let Some((_, true)) = self.bottom.playing {
+            self.toggle_pause();
+        }
+        self.restore_volume(fading_from);
+        self.sleep_timer = None;
+        self.status = "Sleep timer..";
+
+        CONFIG.playback.quit_on_sleep
+    }
+
+    fn restore_volume(&mut self, volume: u8) {
+        let step = volume as i16 - self.playback_behaviour.volume as i16;
+        if let Some(volume) = self.player.change_volume(step as i8) {
+            self.playback_behaviour.volume = volume;
+        }
+    }
+}
+
+impl ui::State<'_> {
+    pub fn remove_music_from_favourates(&mut self, music: &fetcher::MusicUnit) {
+        let query = format!(
+            "
+            DELETE FROM
+            {tb_name} as fav_music
+            WHERE
+            fav_music.id = :id
+        ",
+            tb_name = TB_FAVOURATES_MUSIC
+        );
+        let args = [(":id", &music.id)];
+
+        let res = STORAGE.lock().unwrap().execute(&query, &args);
+        if res.is_ok() {
+            self.status = "Removed..";
+        } else {
+            self.status = "Err removing..";
+        }
+    }
+
+    pub fn remove_playlist_from_favourates(&mut self, playlist: &fetcher::PlaylistUnit) {
+        let query = format!(
             "
             DELETE FROM
             {tb_name} as fav_playlist
@@ -828,10 +2557,12 @@ impl ui::State<'_> {
             tb_name = TB_FAVOURATES_ARTIST
         );
 
+        // Numbers are stored as text. See `fetcher::parse_count_text`
+        let video_count = artist.video_count.to_string();
         let args = [
             (":id", &artist.id),
             (":name", &artist.name),
-            (":count", &artist.video_count),
+            (":count", &video_count),
         ];
 
         let res = STORAGE.lock().unwrap().execute(&query, &args);
@@ -854,11 +2585,13 @@ impl ui::State<'_> {
             tb_name = TB_FAVOURATES_MUSIC
         );
 
+        // Duration is stored as number of seconds in text. See `fetcher::parse_duration_text`
+        let duration = music.duration.as_secs().to_string();
         let args = [
             (":id", &music.id),
             (":title", &music.name),
             (":author", &music.artist),
-            (":duration", &music.duration),
+            (":duration", &duration),
         ];
 
         let res = STORAGE.lock().unwrap().execute(&query, &args);
@@ -879,11 +2612,12 @@ impl ui::State<'_> {
             tb_name = TB_FAVOURATES_PLAYLIST
         );
 
+        let video_count = playlist.video_count.to_string();
         let args = [
             (":id", &playlist.id),
             (":name", &playlist.name),
             (":author", &playlist.author),
-            (":count", &playlist.video_count),
+            (":count", &video_count),
         ];
 
         let res = STORAGE.lock().unwrap().execute(&query, &args);
@@ -924,17 +2658,603 @@ impl ui::Window {
     }
 }
 
-impl std::convert::TryFrom<usize> for ui::SidebarOption {
-    type Error = &'static str;
-    fn try_from(value: usize) -> Result<Self, Self::Error> {
+impl ui::Pagination {
+    // false once it is known that there is no page after the last loaded one
+    pub fn has_more(&self) -> bool {
+        match (self.loaded, self.last) {
+            (Some((_, loaded)), Some(last)) => loaded < last,
+            _ => true,
+        }
+    }
+
+    // Every page of the result is in the list
+    fn is_complete(&self) -> bool {
+        matches!(self.loaded, Some((0, _))) && !self.has_more()
+    }
+
+    // Page to fetch so that rows around cursor at `row` are in the list. None if nothing is left
+    // on that side or some page is already being fetched
+    fn page_to_load(&self, row: usize, len: usize) -> Option<usize> {
+        let (first, loaded) = self.loaded?;
+        if self.requested.is_some() {
+            None
+        } else if row + PRELOAD_ROWS >= len && self.has_more() {
+            Some(loaded + 1)
+        } else if row < PRELOAD_ROWS && first > 0 {
+            Some(first - 1)
+        } else {
+            None
+        }
+    }
+
+    // Move the cursor of list by `rows` (negative to move up). Cursor wraps around only when
+    // whole result is in the list, else it stops at the end until next page is fetched.
+    // Return the page that should be fetched now
+    pub fn scroll<T>(&self, list: &mut (Vec<T>, TableState), rows: isize) -> Option<usize> {
+        let len = list.0.len();
+        if len == 0 {
+            return None;
+        }
+        let last_row = len - 1;
+        let row = match list.1.selected() {
+            None => 0,
+            Some(current) => {
+                let target = current as isize + rows;
+                if target < 0 {
+                    if current == 0 && self.is_complete() {
+                        last_row
+                    } else {
+                        0
+                    }
+                } else if target as usize > last_row {
+                    if current == last_row && self.is_complete() {
+                        0
+                    } else {
+                        last_row
+                    }
+                } else {
+                    target as usize
+                }
+            }
+        };
+        list.1.select(Some(row));
+        self.page_to_load(row, len)
+    }
+
+    // Cursor may have been parked at the edge of list while the page was being fetched and could
+    // not ask for the next one. Remember the page that is still needed so that it is fetched
+    // without waiting for cursor to move again
+    pub fn follow_up<T>(&mut self, list: &(Vec<T>, TableState)) {
+        self.follow_up = list
+            .1
+            .selected()
+            .and_then(|row| self.page_to_load(row, list.0.len()));
+    }
+
+    // Requested page is fetched. It is appended or prepended to the list if it is next to loaded
+    // pages, otherwise list is replaced with it. Page with less than `per_page` item is the last
+    // one as next page would be empty anyway
+    pub fn fill<T>(&mut self, list: &mut (Vec<T>, TableState), mut data: Vec<T>, per_page: usize) {
+        let page = match self.requested.take() {
+            Some(page) => page,
+            None => return,
+        };
+        let count = data.len();
+
+        match self.loaded {
+            Some((first, loaded)) if page == loaded + 1 => {
+                list.0.append(&mut data);
+                self.loaded = Some((first, page));
+                // Drop the first page. Every page except the last one is full
+                if page - first >= MAX_LOADED_PAGES {
+                    let dropped = per_page.min(list.0.len());
+                    list.0.drain(..dropped);
+                    self.loaded = Some((first + 1, page));
+                    let row = list.1.selected().map(|row| row.saturating_sub(dropped));
+                    list.1.select(row);
+                }
+            }
+            Some((first, loaded)) if page + 1 == first => {
+                data.append(&mut list.0);
+                list.0 = data;
+                self.loaded = Some((page, loaded));
+                let row = list.1.selected().map(|row| row + count);
+                list.1.select(row);
+                // Drop the last page
+                if loaded - page >= MAX_LOADED_PAGES {
+                    list.0.truncate((loaded - page) * per_page);
+                    self.loaded = Some((page, loaded - 1));
+                    let last_row = list.0.len().saturating_sub(1);
+                    list.1.select(row.map(|row| row.min(last_row)));
+                }
+            }
+            _ => {
+                list.0 = data;
+                self.loaded = Some((page, page));
+                list.1.select((count > 0).then_some(0));
+            }
+        }
+
+        if count < per_page && matches!(self.loaded, Some((_, loaded)) if loaded == page) {
+            self.last = Some(page);
+        }
+    }
+
+    // Requested page have no result so page before it is the last one and list is kept as it is.
+    // Return true if even the first page have no result
+    pub fn reached_end(&mut self) -> bool {
+        match self.requested.take() {
+            Some(page) if page > 0 => {
+                self.last = Some(page - 1);
+                false
+            }
+            _ => {
+                self.loaded = Some((0, 0));
+                self.last = Some(0);
+                true
+            }
+        }
+    }
+
+    // Page where the cursor is at. First loaded page if there is no cursor
+    fn page_at(&self, row: Option<usize>, per_page: usize) -> Option<usize> {
+        let (first, loaded) = self.loaded?;
+        let page = first + row.unwrap_or_default() / per_page.max(1);
+        Some(page.min(loaded))
+    }
+
+    // Like "page 3/5" or "page 3+" when last page is not known yet
+    fn describe(&self, row: Option<usize>, per_page: usize) -> Option<String> {
+        let page = self.page_at(row, per_page)?;
+        Some(match self.last {
+            Some(last) => format!("page {}/{}", page + 1, last + 1),
+            None => format!("page {}+", page + 1),
+        })
+    }
+}
+
+// Title of the music/playlist/artist list along with the page the cursor is at
+fn list_title(name: &str, pagination: &ui::Pagination, row: Option<usize>) -> String {
+    match pagination.describe(row, CONFIG.constants.item_per_list) {
+        Some(page) => format!("{} ({}) ", name, page),
+        None => format!("{} ", name),
+    }
+}
+
+impl std::convert::From<usize> for ui::SidebarOption {
+    fn from(value: usize) -> Self {
         match value {
-            0 => Ok(ui::SidebarOption::Trending),
-            1 => Ok(ui::SidebarOption::YoutubeCommunity),
-            2 => Ok(ui::SidebarOption::Liked),
-            3 => Ok(ui::SidebarOption::Saved),
-            4 => Ok(ui::SidebarOption::Following),
-            5 => Ok(ui::SidebarOption::Search),
-            _ => Err("No sidebar option found corresponding to this usize"),
+            0 => ui::SidebarOption::Trending,
+            1 => ui::SidebarOption::Liked,
+            2 => ui::SidebarOption::Saved,
+            3 => ui::SidebarOption::Following,
+            4 => ui::SidebarOption::Search,
+            _ => ui::SidebarOption::Community(value - SIDEBAR_LIST_COUNT),
+        }
+    }
+}
+
+// Queries searched before, latest first
+fn read_search_history() -> Vec<String> {
+    let conn = STORAGE.lock().unwrap();
+    let query = format!(
+        "SELECT query FROM {tb_history} ORDER BY searched_at DESC LIMIT {limit}",
+        tb_history = TB_SEARCH_HISTORY,
+        limit = MAX_SEARCH_HISTORY
+    );
+    let mut stmt = match conn.prepare(&query) {
+        Ok(val) => val,
+        Err(err) => {
+            eprintln!(
+                "Error preparing select statement for search history. Error: {err}",
+                err = err
+            );
+            return Vec::new();
+        }
+    };
+    let history = match stmt.query_map([], |row| row.get::<_, String>(0)) {
+        Ok(rows) => rows.filter_map(Result::ok).collect(),
+        Err(_) => Vec::new(),
+    };
+    history
+}
+
+// Previous queries that start with typed text followed by suggestions of server. Nothing is
+// repeated and text exactly as typed is not a completion
+fn search_completions<'a>(
+    typed: &str,
+    history: &'a [String],
+    suggestions: &'a [String],
+) -> Vec<&'a str> {
+    let typed = typed.trim().to_lowercase();
+    if typed.is_empty() {
+        return Vec::new();
+    }
+
+    let mut completions: Vec<&str> = history
+        .iter()
+        .filter(|previous| {
+            let previous = previous.to_lowercase();
+            previous.starts_with(&typed) && previous != typed
+        })
+        .take(HISTORY_COMPLETIONS)
+        .map(String::as_str)
+        .collect();
+    for suggestion in suggestions {
+        if completions.len() >= MAX_COMPLETIONS {
+            break;
+        }
+        let is_repeated = completions
+            .iter()
+            .any(|completion| completion.eq_ignore_ascii_case(suggestion));
+        if !is_repeated && suggestion.to_lowercase() != typed {
+            completions.push(suggestion);
+        }
+    }
+    completions
+}
+
+// Choose the videos to be added by autoplay from related videos. Related videos are already in
+// order of relevance but videos whose length is far from usual song length are pushed behind and
+// the ones outside of [min_secs, max_secs] are skipped as those are most likely not music
+fn pick_autoplay(
+    recommended: Vec<fetcher::MusicUnit>,
+    history: &HashSet<String>,
+    min_secs: u64,
+    max_secs: u64,
+) -> Vec<fetcher::MusicUnit> {
+    let mut scored = recommended
+        .into_iter()
+        .enumerate()
+        .filter(|(_, music)| !history.contains(&music.id))
+        .filter_map(|(rank, music)| {
+            let secs = music.duration.as_secs();
+            if secs < min_secs || secs > max_secs {
+                return None;
+            }
+            let score = rank as f64 + (secs as f64 - TYPICAL_SONG_SECS).abs() / 60.0;
+            Some((score, music))
+        })
+        .collect::<Vec<_>>();
+
+    scored.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    scored
+        .into_iter()
+        .map(|(_, music)| music)
+        .take(AUTOPLAY_BATCH)
+        .collect()
+}
+
+// Options shown in sleep timer picker. See `SLEEP_PICKER_TITLE`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SleepOption {
+    Off,
+    Minutes(u64),
+    EndOfTrack,
+    EndOfQueue,
+}
+
+impl SleepOption {
+    fn of(at: ui::SleepAt) -> Self {
+        match at {
+            ui::SleepAt::Time(_, minutes) => SleepOption::Minutes(minutes),
+            ui::SleepAt::EndOfTrack(_) => SleepOption::EndOfTrack,
+            ui::SleepAt::EndOfQueue(_) => SleepOption::EndOfQueue,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            SleepOption::Off => "Off".to_string(),
+            SleepOption::Minutes(minutes) => format!("{} min", minutes),
+            SleepOption::EndOfTrack => "End of track".to_string(),
+            SleepOption::EndOfQueue => "End of queue".to_string(),
+        }
+    }
+}
+
+// Off, presets of minutes along with typed one in order and then the end of track and queue
+fn sleep_options(typed_minutes: u64) -> Vec<SleepOption> {
+    let mut minutes = SLEEP_TIMER_MINUTES.to_vec();
+    if typed_minutes > 0 && !minutes.contains(&typed_minutes) {
+        minutes.push(typed_minutes);
+        minutes.sort_unstable();
+    }
+
+    std::iter::once(SleepOption::Off)
+        .chain(minutes.into_iter().map(SleepOption::Minutes))
+        .chain([SleepOption::EndOfTrack, SleepOption::EndOfQueue])
+        .collect()
+}
+
+// Remember the position of last track of queue once it starts playing. Forget it if more tracks
+// are added while it is playing. See `ui::SleepAt::EndOfQueue`
+fn reach_queue_end(at: ui::SleepAt, player: &dyn ExtendMpv) -> ui::SleepAt {
+    let last = match at {
+        ui::SleepAt::EndOfQueue(last) => last,
+        _ => return at,
+    };
+    let position = player.get_prop_i64("playlist-pos");
+    let count = player.get_prop_i64("playlist-count");
+    match (position, count) {
+        (Some(position), Some(count)) if position >= 0 && position + 1 == count => {
+            ui::SleepAt::EndOfQueue(Some(position))
         }
+        (Some(position), _) if Some(position) == last => ui::SleepAt::EndOfQueue(None),
+        _ => at,
+    }
+}
+
+// Time left for sleep timer to go off given time left for playing track to end. None if it
+// cannot be known yet
+fn sleep_remaining(
+    at: ui::SleepAt,
+    player: &dyn ExtendMpv,
+    track_remaining: Option<Duration>,
+) -> Option<Duration> {
+    let position = player.get_prop_i64("playlist-pos");
+    match at {
+        ui::SleepAt::Time(at, _) => Some(at.saturating_duration_since(Instant::now())),
+        // Track have already changed so it have ended. When repeating the queue, player wraps to
+        // first track after the last one
+        ui::SleepAt::EndOfTrack(playing) | ui::SleepAt::EndOfQueue(Some(playing)) => {
+            if position != Some(playing) {
+                Some(Duration::ZERO)
+            } else {
+                track_remaining
+            }
+        }
+        ui::SleepAt::EndOfQueue(None) => match position {
+            // Player is idle (position -1) as nothing is left in the queue
+            None => Some(Duration::ZERO),
+            Some(position) if position < 0 => Some(Duration::ZERO),
+            Some(_) => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    // Player that only reports the properties set by test
+    #[derive(Default)]
+    struct FakePlayer(RefCell<HashMap<String, String>>);
+
+    impl FakePlayer {
+        fn playing(&self, position: i64, count: i64) {
+            let mut props = self.0.borrow_mut();
+            props.insert("playlist-pos".to_string(), position.to_string());
+            props.insert("playlist-count".to_string(), count.to_string());
+        }
+    }
+
+    impl ExtendMpv for FakePlayer {
+        fn set_prop(&self, name: &str, value: &str) -> bool {
+            self.0
+                .borrow_mut()
+                .insert(name.to_string(), value.to_string());
+            true
+        }
+
+        fn get_prop(&self, name: &str) -> Option<String> {
+            self.0.borrow().get(name).cloned()
+        }
+
+        fn run_command(&self, _: &str, _: &[&str]) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn sleep_at_end_of_queue() {
+        let player = FakePlayer::default();
+        let track_remaining = Some(Duration::from_secs(20));
+        let tick = |at| {
+            let at = reach_queue_end(at, &player);
+            (at, sleep_remaining(at, &player, track_remaining))
+        };
+
+        // Not known until last track starts
+        player.playing(1, 3);
+        let (at, remaining) = tick(ui::SleepAt::EndOfQueue(None));
+        assert_eq!(remaining, None);
+
+        // More tracks were added while last one was playing
+        player.playing(2, 3);
+        let (at, remaining) = tick(at);
+        assert_eq!(remaining, track_remaining);
+        player.playing(2, 5);
+        let (at, remaining) = tick(at);
+        assert_eq!(at, ui::SleepAt::EndOfQueue(None));
+        assert_eq!(remaining, None);
+
+        // Repeating queue wraps to first track after the last one
+        player.playing(4, 5);
+        let (at, remaining) = tick(at);
+        assert_eq!(at, ui::SleepAt::EndOfQueue(Some(4)));
+        assert_eq!(remaining, track_remaining);
+        player.playing(0, 5);
+        assert_eq!(tick(at).1, Some(Duration::ZERO));
+
+        // Player goes idle when not repeating
+        player.playing(-1, 5);
+        assert_eq!(tick(at).1, Some(Duration::ZERO));
+        assert_eq!(tick(ui::SleepAt::EndOfQueue(None)).1, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn typed_sleep_minutes_are_in_order() {
+        assert_eq!(sleep_options(0).len(), SLEEP_TIMER_MINUTES.len() + 3);
+        let options = sleep_options(20);
+        assert_eq!(options[1], SleepOption::Minutes(15));
+        assert_eq!(options[2], SleepOption::Minutes(20));
+        assert_eq!(options[3], SleepOption::Minutes(30));
+        assert_eq!(sleep_options(30).len(), SLEEP_TIMER_MINUTES.len() + 3);
+    }
+
+    #[test]
+    fn pagination_survives_end_of_result() {
+        let mut list = (Vec::new(), TableState::default());
+        let mut pages = ui::Pagination {
+            requested: Some(0),
+            ..Default::default()
+        };
+        pages.fill(&mut list, (0..10).collect::<Vec<usize>>(), 10);
+        assert_eq!(pages.describe(Some(0), 10).unwrap(), "page 1+");
+
+        // Next page is asked once cursor gets near the end and is appended
+        assert_eq!(pages.scroll(&mut list, 6), None);
+        assert_eq!(pages.scroll(&mut list, 1), Some(1));
+        pages.requested = Some(1);
+        pages.fill(&mut list, (10..20).collect(), 10);
+        assert_eq!(list.0.len(), 20);
+        assert_eq!(list.1.selected(), Some(7));
+
+        // Page 2 is empty so page 1 is the last one and list is kept as it is
+        assert_eq!(pages.scroll(&mut list, 100), Some(2));
+        assert_eq!(list.1.selected(), Some(19));
+        pages.requested = Some(2);
+        assert!(!pages.reached_end());
+        assert_eq!(list.0.len(), 20);
+        assert_eq!(pages.describe(list.1.selected(), 10).unwrap(), "page 2/2");
+
+        // Whole result is loaded so cursor wraps around
+        assert_eq!(pages.scroll(&mut list, 1), None);
+        assert_eq!(list.1.selected(), Some(0));
+
+        // Short page is the last one
+        let mut pages = ui::Pagination {
+            requested: Some(0),
+            ..Default::default()
+        };
+        pages.fill(&mut (Vec::new(), TableState::default()), vec![1, 2, 3], 10);
+        assert!(!pages.has_more());
+
+        let mut pages = ui::Pagination {
+            requested: Some(0),
+            ..Default::default()
+        };
+        assert!(pages.reached_end());
+        assert_eq!(pages.describe(None, 10).unwrap(), "page 1/1");
+    }
+
+    #[test]
+    fn small_pages_are_followed_up() {
+        let mut list = (Vec::new(), TableState::default());
+        let mut pages = ui::Pagination {
+            requested: Some(0),
+            ..Default::default()
+        };
+        pages.fill(&mut list, vec![0, 1], 2);
+        // Cursor stays at the end while next page is being fetched
+        assert_eq!(pages.scroll(&mut list, 1), Some(1));
+        pages.requested = Some(1);
+        assert_eq!(pages.scroll(&mut list, 1), None);
+
+        // Page that arrived is not enough to move cursor away from the end
+        pages.fill(&mut list, vec![2, 3], 2);
+        pages.follow_up(&list);
+        assert_eq!(pages.follow_up, Some(2));
+    }
+
+    #[test]
+    fn pagination_keeps_memory_bounded() {
+        let mut list = (Vec::new(), TableState::default());
+        let mut pages = ui::Pagination::default();
+        for page in 0..=MAX_LOADED_PAGES {
+            pages.requested = Some(page);
+            pages.fill(&mut list, vec![page; 2], 2);
+        }
+        // First page is dropped
+        assert_eq!(list.0.len(), MAX_LOADED_PAGES * 2);
+        assert_eq!(list.0[0], 1);
+        assert_eq!(pages.loaded, Some((1, MAX_LOADED_PAGES)));
+
+        // Going back to top brings the first page back and drops the last one
+        list.1.select(Some(1));
+        assert_eq!(pages.scroll(&mut list, -1), Some(0));
+        pages.requested = Some(0);
+        pages.fill(&mut list, vec![0; 2], 2);
+        assert_eq!(list.0[0], 0);
+        assert_eq!(list.1.selected(), Some(2));
+        assert_eq!(list.0.len(), MAX_LOADED_PAGES * 2);
+        assert_eq!(pages.loaded, Some((0, MAX_LOADED_PAGES - 1)));
+        assert_eq!(pages.describe(Some(2), 2).unwrap(), "page 2+");
+    }
+
+    #[test]
+    fn autoplay_skips_played_and_non_music() {
+        let music = |id: &str, duration: u64| fetcher::MusicUnit {
+            artist: String::new(),
+            name: String::new(),
+            duration: Duration::from_secs(duration),
+            id: id.to_string(),
+            ..Default::default()
+        };
+        let recommended = vec![
+            music("played", 210),
+            music("short", 40),
+            music("mix", 3720),
+            music("long-song", 540),
+            music("song", 190),
+        ];
+        let history = HashSet::from(["played".to_string()]);
+
+        let chosen = pick_autoplay(recommended, &history, 60, 900)
+            .into_iter()
+            .map(|music| music.id)
+            .collect::<Vec<_>>();
+        assert_eq!(chosen, ["song", "long-song"]);
+    }
+
+    #[test]
+    fn short_counts() {
+        assert_eq!(short_count(999), "999");
+        assert_eq!(short_count(1_250), "1.2K");
+        assert_eq!(short_count(3_400_000), "3.4M");
+        assert_eq!(short_count(2_000_000_000), "2.0B");
+    }
+
+    #[test]
+    fn extract_youtube_id() {
+        assert_eq!(
+            youtube_id_of("https://www.youtube.com/watch?v=abc123"),
+            Some("abc123")
+        );
+        assert_eq!(
+            youtube_id_of("https://www.youtube.com/watch?list=PL1&v=abc123&index=2"),
+            Some("abc123")
+        );
+        assert_eq!(youtube_id_of("ytdl://abc123"), Some("abc123"));
+        assert_eq!(
+            youtube_id_of("https://youtu.be/abc123?t=10"),
+            Some("abc123")
+        );
+        assert_eq!(
+            youtube_id_of("https://www.youtube.com/watch?list=PL1"),
+            None
+        );
+        assert_eq!(
+            youtube_id_of("https://rr1.googlevideo.com/videoplayback"),
+            None
+        );
+    }
+
+    #[test]
+    fn completions_put_history_first() {
+        let strings = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let history = strings(&["lofi beats", "Lofi hip hop", "rock", "lofi", "lofi girl"]);
+        let suggestions = strings(&["lofi hip hop", "lofi", "lofi radio"]);
+
+        // Only matching history and at most HISTORY_COMPLETIONS of it. Nothing is repeated and
+        // text as typed is skipped
+        assert_eq!(
+            search_completions("Lofi", &history, &suggestions),
+            vec!["lofi beats", "Lofi hip hop", "lofi girl", "lofi radio"]
+        );
+        assert!(search_completions("  ", &history, &suggestions).is_empty());
+        assert_eq!(search_completions("ro", &history, &[]), vec!["rock"]);
     }
 }